@@ -2,11 +2,13 @@
 
 set -e
 
-cargo build --target $TARGET --all-features --release
+# The `std` feature can't be built for bare metal targets
+cargo build --target $TARGET --features graphics,async --release
 
 if [ -z $DISABLE_EXAMPLES ]; then
-	cargo build --target $TARGET --all-features --examples
+	cargo build --target $TARGET --features graphics,async --examples
 fi
 
 cargo test --lib --target x86_64-unknown-linux-gnu
-cargo test --doc --target x86_64-unknown-linux-gnu
+cargo test --lib --target x86_64-unknown-linux-gnu --all-features
+cargo test --doc --target x86_64-unknown-linux-gnu --all-features
@@ -38,6 +38,68 @@
 //!     .connect_i2c(i2c);
 //! ```
 //!
+//! Connect to an `embedded-hal` 1.0 SPI device. The device owns the Chip Select pin, so only the
+//! Data/Command pin is given to the builder:
+//!
+//! ```rust,no_run
+//! use sh1106::Builder;
+//! let spi = /* SpiDevice from your HAL of choice */
+//! # sh1106::test_helpers::SpiStub;
+//! let dc = /* GPIO data/command select pin */
+//! # sh1106::test_helpers::PinStub;
+//!
+//! Builder::new().connect_spi_device(spi, dc);
+//! ```
+//!
+//! Connect using any interface from the
+//! [`display-interface`](https://crates.io/crates/display-interface) ecosystem, e.g. a parallel
+//! GPIO bus:
+//!
+//! ```rust,no_run
+//! use sh1106::{mode::GraphicsMode, Builder};
+//! let iface = /* Any display_interface::WriteOnlyDataCommand implementation */
+//! # sh1106::test_helpers::DataCommandStub;
+//!
+//! let display: GraphicsMode<_> = Builder::new().connect(iface).into();
+//! ```
+//!
+//! With the `async` feature enabled, `embedded-hal-async` peripherals can be used with
+//! `connect_i2c_async` and `connect_spi_async`:
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "async")]
+//! # async fn run() {
+//! use sh1106::{mode::GraphicsMode, Builder};
+//! let i2c = /* Async I2C interface from your HAL of choice */
+//! # sh1106::test_helpers::I2cStub;
+//!
+//! let mut display: GraphicsMode<_> = Builder::new().connect_i2c_async(i2c).into();
+//!
+//! display.init_async().await.unwrap();
+//! display.flush_async().await.unwrap();
+//! # }
+//! ```
+//!
+//! Give the builder the display's reset pin and a delay to reset the display every time it is
+//! initialised:
+//!
+//! ```rust,no_run
+//! use sh1106::{mode::GraphicsMode, Builder};
+//! let i2c = /* I2C interface from your HAL of choice */
+//! # sh1106::test_helpers::I2cStub;
+//! let rst = /* GPIO reset pin */
+//! # sh1106::test_helpers::PinStub;
+//! let delay = /* Delay provider from your HAL of choice */
+//! # sh1106::test_helpers::DelayStub;
+//!
+//! let mut display: GraphicsMode<_> = Builder::new()
+//!     .with_reset(rst, delay)
+//!     .connect_i2c(i2c)
+//!     .into();
+//!
+//! display.init().unwrap();
+//! ```
+//!
 //! The above examples will produce a [RawMode](../mode/raw/struct.RawMode.html) instance
 //! by default. You need to coerce them into a mode by specifying a type on assignment. For
 //! example, to use [`GraphicsMode` mode](../mode/graphics/struct.GraphicsMode.html):
@@ -61,7 +123,12 @@ use hal::{self, digital::v2::OutputPin};
 use crate::{
     displayrotation::DisplayRotation,
     displaysize::DisplaySize,
-    interface::{I2cInterface, SpiInterface},
+    initconfig::InitConfig,
+    interface::{
+        reset::{Hal1Delay, Hal1Pin, HardwareReset, WithPinError},
+        DataCommandInterface, I2cDeviceInterface, I2cInterface, ResetInterface, SpiDeviceInterface,
+        SpiInterface,
+    },
     mode::{displaymode::DisplayMode, raw::RawMode},
     properties::DisplayProperties,
 };
@@ -70,10 +137,14 @@ use crate::{
 ///
 /// See the [module level documentation](crate::builder) for more details.
 #[derive(Clone, Copy)]
-pub struct Builder {
+pub struct Builder<RST = NoReset> {
     display_size: DisplaySize,
+    size_configured: bool,
     rotation: DisplayRotation,
+    flip: (bool, bool),
     i2c_addr: u8,
+    init_config: InitConfig,
+    reset: RST,
 }
 
 impl Default for Builder {
@@ -87,17 +158,25 @@ impl Builder {
     pub fn new() -> Builder {
         Builder {
             display_size: DisplaySize::Display128x64,
+            size_configured: false,
             rotation: DisplayRotation::Rotate0,
+            flip: (false, false),
             i2c_addr: 0x3c,
+            init_config: InitConfig::new(),
+            reset: NoReset,
         }
     }
 }
 
-impl Builder {
+impl<RST> Builder<RST> {
     /// Set the size of the display. Supported sizes are defined by [DisplaySize].
+    ///
+    /// Modes sized for a particular panel, like `GraphicsMode<_, Size128x32>`, panic when created
+    /// if this size doesn't match their own.
     pub fn with_size(self, display_size: DisplaySize) -> Self {
         Self {
             display_size,
+            size_configured: true,
             ..self
         }
     }
@@ -113,17 +192,127 @@ impl Builder {
         Self { rotation, ..self }
     }
 
+    /// Mirror the display horizontally (`flip_x`) and/or vertically (`flip_y`), e.g. for viewing
+    /// it through a reflection. Mirroring is independent of the rotation and applies to the axes
+    /// of the rotated display. Defaults to no mirroring.
+    pub fn with_flip(self, flip_x: bool, flip_y: bool) -> Self {
+        Self {
+            flip: (flip_x, flip_y),
+            ..self
+        }
+    }
+
+    /// Set the parameters of the initialisation sequence, like clock, precharge and charge pump
+    /// settings. Defaults to [`InitConfig::new`].
+    pub fn with_init_config(self, init_config: InitConfig) -> Self {
+        Self {
+            init_config,
+            ..self
+        }
+    }
+
+    /// Reset the display through its reset pin whenever it is initialised, using `delay` to time
+    /// the reset pulse. Every `init` method then performs the reset itself, and pin errors are
+    /// reported as [`Error::Pin`](crate::Error::Pin) like those of the interface.
+    ///
+    /// `rst` is an `embedded-hal` 0.2 pin, use [`with_reset_hal1`](Self::with_reset_hal1) for
+    /// `embedded-hal` 1.0 pins. `delay` is a blocking [`DelayMs`](hal::blocking::delay::DelayMs)
+    /// for the blocking interfaces, and an `embedded-hal-async` `DelayNs` for the asynchronous
+    /// ones.
+    ///
+    /// With SPI, the reset pin must have the same error type as the DC and CS pins. Interfaces
+    /// without pins, like I2C, report errors with the error type of the reset pin.
+    pub fn with_reset<PIN, DELAY>(
+        self,
+        rst: PIN,
+        delay: DELAY,
+    ) -> Builder<HardwareReset<PIN, DELAY>>
+    where
+        PIN: OutputPin,
+    {
+        Builder {
+            display_size: self.display_size,
+            size_configured: self.size_configured,
+            rotation: self.rotation,
+            flip: self.flip,
+            i2c_addr: self.i2c_addr,
+            init_config: self.init_config,
+            reset: HardwareReset::new(rst, delay),
+        }
+    }
+
+    /// Like [`with_reset`](Self::with_reset), for a reset pin and delay implementing the
+    /// `embedded-hal` 1.0 traits. `delay` is an `embedded-hal` `DelayNs` for the blocking
+    /// interfaces, and an `embedded-hal-async` `DelayNs` for the asynchronous ones.
+    ///
+    /// ```rust
+    /// use sh1106::{prelude::*, Builder};
+    /// # let (i2c, rst, delay) = (
+    /// #     sh1106::test_helpers::I2cStub,
+    /// #     sh1106::test_helpers::PinStub,
+    /// #     sh1106::test_helpers::DelayStub,
+    /// # );
+    ///
+    /// let mut display: GraphicsMode<_> = Builder::new()
+    ///     .with_reset_hal1(rst, delay)
+    ///     .connect_i2c_device(i2c)
+    ///     .into();
+    ///
+    /// display.init().unwrap();
+    /// ```
+    pub fn with_reset_hal1<PIN, DELAY>(
+        self,
+        rst: PIN,
+        delay: DELAY,
+    ) -> Builder<HardwareReset<Hal1Pin<PIN>, Hal1Delay<DELAY>>>
+    where
+        PIN: embedded_hal_1::digital::OutputPin,
+    {
+        self.with_reset(Hal1Pin::new(rst), Hal1Delay::new(delay))
+    }
+
+    fn properties<DI>(self, attach: impl FnOnce(RST) -> DI) -> DisplayProperties<DI> {
+        let iface = attach(self.reset);
+        let mut properties = DisplayProperties::new(iface, self.display_size, self.rotation);
+        properties.set_size_configured(self.size_configured);
+        properties.set_init_config(self.init_config);
+        properties.set_flip_config(self.flip.0, self.flip.1);
+
+        properties
+    }
+
+    /// Finish the builder and use any [`display-interface`](https://crates.io/crates/display-interface)
+    /// implementation to communicate with the display
+    pub fn connect<DI>(self, iface: DI) -> DisplayMode<RawMode<RST::PinlessInterface>>
+    where
+        RST: AttachReset<DataCommandInterface<DI>>,
+        DI: display_interface::WriteOnlyDataCommand,
+    {
+        let properties =
+            self.properties(|reset| reset.attach_pinless(DataCommandInterface::new(iface)));
+        DisplayMode::new(properties)
+    }
+
+    /// Finish the builder and use any [`DisplayInterface`](crate::interface::DisplayInterface)
+    /// implementation to communicate with the display, e.g. a custom interface or the `Emulator`
+    /// enabled by the `std` feature
+    pub fn connect_interface<DI>(self, iface: DI) -> DisplayMode<RawMode<RST::Interface>>
+    where
+        RST: AttachReset<DI>,
+    {
+        let properties = self.properties(|reset| reset.attach(iface));
+        DisplayMode::new(properties)
+    }
+
     /// Finish the builder and use I2C to communicate with the display
-    pub fn connect_i2c<I2C, CommE>(self, i2c: I2C) -> DisplayMode<RawMode<I2cInterface<I2C>>>
+    pub fn connect_i2c<I2C, CommE>(self, i2c: I2C) -> DisplayMode<RawMode<RST::PinlessInterface>>
     where
+        RST: AttachReset<I2cInterface<I2C>>,
         I2C: hal::blocking::i2c::Write<Error = CommE>,
     {
-        let properties = DisplayProperties::new(
-            I2cInterface::new(i2c, self.i2c_addr),
-            self.display_size,
-            self.rotation,
-        );
-        DisplayMode::<RawMode<I2cInterface<I2C>>>::new(properties)
+        let iface = I2cInterface::new(i2c, self.i2c_addr);
+        let properties = self.properties(|reset| reset.attach_pinless(iface));
+        DisplayMode::new(properties)
     }
 
     /// Finish the builder and use SPI to communicate with the display
@@ -136,19 +325,155 @@ impl Builder {
         spi: SPI,
         dc: DC,
         cs: CS,
-    ) -> DisplayMode<RawMode<SpiInterface<SPI, DC, CS>>>
+    ) -> DisplayMode<RawMode<RST::Interface>>
     where
+        RST: AttachReset<SpiInterface<SPI, DC, CS>>,
         SPI: hal::blocking::spi::Transfer<u8, Error = CommE>
             + hal::blocking::spi::Write<u8, Error = CommE>,
         DC: OutputPin<Error = PinE>,
         CS: OutputPin<Error = PinE>,
     {
-        let properties = DisplayProperties::new(
-            SpiInterface::new(spi, dc, cs),
-            self.display_size,
-            self.rotation,
-        );
-        DisplayMode::<RawMode<SpiInterface<SPI, DC, CS>>>::new(properties)
+        let properties = self.properties(|reset| reset.attach(SpiInterface::new(spi, dc, cs)));
+        DisplayMode::new(properties)
+    }
+
+    /// Finish the builder and use an `embedded-hal` 1.0 I2C peripheral to communicate with the
+    /// display
+    pub fn connect_i2c_device<I2C>(self, i2c: I2C) -> DisplayMode<RawMode<RST::PinlessInterface>>
+    where
+        RST: AttachReset<I2cDeviceInterface<I2C>>,
+        I2C: embedded_hal_1::i2c::I2c,
+    {
+        let iface = I2cDeviceInterface::new(i2c, self.i2c_addr);
+        let properties = self.properties(|reset| reset.attach_pinless(iface));
+        DisplayMode::new(properties)
+    }
+
+    /// Finish the builder and use an `embedded-hal` 1.0 SPI device to communicate with the display
+    ///
+    /// Chip Select is handled by the [`SpiDevice`](embedded_hal_1::spi::SpiDevice) implementation,
+    /// so only the Data/Command pin is passed here.
+    pub fn connect_spi_device<SPI, DC>(
+        self,
+        spi: SPI,
+        dc: DC,
+    ) -> DisplayMode<RawMode<RST::Interface>>
+    where
+        RST: AttachReset<SpiDeviceInterface<SPI, DC>>,
+        SPI: embedded_hal_1::spi::SpiDevice,
+        DC: embedded_hal_1::digital::OutputPin,
+    {
+        let properties = self.properties(|reset| reset.attach(SpiDeviceInterface::new(spi, dc)));
+        DisplayMode::new(properties)
+    }
+
+    /// Finish the builder and use any asynchronous
+    /// [`display-interface`](https://crates.io/crates/display-interface) implementation to
+    /// communicate with the display
+    #[cfg(feature = "async")]
+    pub fn connect_async<DI>(self, iface: DI) -> DisplayMode<RawMode<RST::PinlessInterface>>
+    where
+        RST: AttachReset<DataCommandInterface<DI>>,
+        DI: display_interface::AsyncWriteOnlyDataCommand,
+    {
+        let properties =
+            self.properties(|reset| reset.attach_pinless(DataCommandInterface::new(iface)));
+        DisplayMode::new(properties)
+    }
+
+    /// Finish the builder and use an `embedded-hal-async` I2C peripheral to communicate with the
+    /// display
+    ///
+    /// The resulting modes expose `_async` variants of their methods, e.g.
+    /// [`GraphicsMode::flush_async`](crate::mode::GraphicsMode::flush_async).
+    #[cfg(feature = "async")]
+    pub fn connect_i2c_async<I2C>(self, i2c: I2C) -> DisplayMode<RawMode<RST::PinlessInterface>>
+    where
+        RST: AttachReset<I2cDeviceInterface<I2C>>,
+        I2C: embedded_hal_async::i2c::I2c,
+    {
+        let iface = I2cDeviceInterface::new(i2c, self.i2c_addr);
+        let properties = self.properties(|reset| reset.attach_pinless(iface));
+        DisplayMode::new(properties)
+    }
+
+    /// Finish the builder and use an `embedded-hal-async` SPI device to communicate with the
+    /// display
+    ///
+    /// The resulting modes expose `_async` variants of their methods, e.g.
+    /// [`GraphicsMode::flush_async`](crate::mode::GraphicsMode::flush_async).
+    #[cfg(feature = "async")]
+    pub fn connect_spi_async<SPI, DC>(
+        self,
+        spi: SPI,
+        dc: DC,
+    ) -> DisplayMode<RawMode<RST::Interface>>
+    where
+        RST: AttachReset<SpiDeviceInterface<SPI, DC>>,
+        SPI: embedded_hal_async::spi::SpiDevice,
+        DC: embedded_hal_1::digital::OutputPin,
+    {
+        let properties = self.properties(|reset| reset.attach(SpiDeviceInterface::new(spi, dc)));
+        DisplayMode::new(properties)
+    }
+}
+
+impl<PIN, DELAY> Builder<HardwareReset<PIN, DELAY>> {
+    /// Set how long the reset pin is held low, and how long to wait after releasing it before
+    /// sending any commands, in milliseconds. Defaults to a 10ms pulse and no wait.
+    pub fn with_reset_timing(self, pulse_ms: u8, settle_ms: u8) -> Self {
+        Self {
+            reset: self.reset.with_timing(pulse_ms, settle_ms),
+            ..self
+        }
+    }
+}
+
+/// Attach a hardware reset, if any, to a display interface
+pub trait AttachReset<DI> {
+    /// Interface the driver communicates through
+    type Interface;
+    /// Interface the driver communicates through, for interfaces without pins of their own
+    type PinlessInterface;
+
+    /// Wrap `iface` so it resets the display when initialised
+    fn attach(self, iface: DI) -> Self::Interface;
+
+    /// Wrap `iface`, which doesn't use any pins, so it resets the display when initialised and
+    /// reports errors with the error type of the reset pin
+    fn attach_pinless(self, iface: DI) -> Self::PinlessInterface;
+}
+
+/// No reset pin, the default for the builder
+#[derive(Debug, Clone, Copy)]
+pub struct NoReset;
+
+impl<DI> AttachReset<DI> for NoReset {
+    type Interface = DI;
+    type PinlessInterface = DI;
+
+    fn attach(self, iface: DI) -> DI {
+        iface
+    }
+
+    fn attach_pinless(self, iface: DI) -> DI {
+        iface
+    }
+}
+
+impl<DI, PIN, DELAY> AttachReset<DI> for HardwareReset<PIN, DELAY>
+where
+    PIN: OutputPin,
+{
+    type Interface = ResetInterface<DI, PIN, DELAY>;
+    type PinlessInterface = ResetInterface<WithPinError<DI, PIN::Error>, PIN, DELAY>;
+
+    fn attach(self, iface: DI) -> Self::Interface {
+        ResetInterface::new(iface, self)
+    }
+
+    fn attach_pinless(self, iface: DI) -> Self::PinlessInterface {
+        ResetInterface::new(WithPinError::new(iface), self)
     }
 }
 
@@ -165,6 +490,12 @@ impl<PinE> NoOutputPin<PinE> {
     }
 }
 
+impl<PinE> Default for NoOutputPin<PinE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<PinE> OutputPin for NoOutputPin<PinE> {
     type Error = PinE;
     fn set_low(&mut self) -> Result<(), PinE> {
@@ -177,7 +508,13 @@ impl<PinE> OutputPin for NoOutputPin<PinE> {
 
 #[cfg(test)]
 mod tests {
-    use super::NoOutputPin;
+    use super::{Builder, NoOutputPin};
+    use crate::{
+        mode::{displaymode::DisplayModeTrait, GraphicsMode},
+        test_helpers::{DelayStub, I2cStub, PinId, Recorder, Transaction},
+        Error,
+    };
+    use core::{cell::Cell, convert::Infallible};
     use embedded_hal::digital::v2::OutputPin;
 
     enum SomeError {}
@@ -194,4 +531,249 @@ mod tests {
 
         assert!(true);
     }
+
+    #[test]
+    fn i2c_device_bytes() {
+        let recorder = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_i2c_addr(0x3d)
+            .connect_i2c_device(recorder.i2c())
+            .into();
+
+        display.set_contrast(0x40).unwrap();
+        assert!(recorder.log().transactions().eq([Transaction::I2cWrite {
+            addr: 0x3d,
+            bytes: &[0x00, 0x81, 0x40]
+        }]));
+
+        display.flush().unwrap();
+        display.set_pixel(0, 0, 1);
+        recorder.clear();
+        display.flush().unwrap();
+        let write = |bytes| Transaction::I2cWrite { addr: 0x3d, bytes };
+        assert!(recorder.log().transactions().take(4).eq([
+            write(&[0x00, 0xB0]),
+            write(&[0x00, 0x02]),
+            write(&[0x00, 0x10]),
+            write(&[0x40, 0x01]),
+        ]));
+    }
+
+    #[test]
+    fn spi_device_bytes() {
+        let recorder = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new()
+            .connect_spi_device(recorder.spi(), recorder.pin(PinId::Dc))
+            .into();
+
+        // Chip Select is driven by the SPI device around every write
+        display.set_contrast(0x40).unwrap();
+        assert!(recorder.log().transactions().eq([
+            Transaction::Pin {
+                pin: PinId::Dc,
+                high: false
+            },
+            Transaction::Pin {
+                pin: PinId::Cs,
+                high: false
+            },
+            Transaction::SpiWrite {
+                dc: false,
+                cs: false,
+                bytes: &[0x81, 0x40]
+            },
+            Transaction::Pin {
+                pin: PinId::Cs,
+                high: true
+            },
+            Transaction::Pin {
+                pin: PinId::Dc,
+                high: true
+            },
+        ]));
+
+        display.flush().unwrap();
+        display.set_pixel(0, 0, 1);
+        recorder.clear();
+        display.flush().unwrap();
+        // The data phase is the only write with DC high, inside its own CS frame
+        let log = recorder.log();
+        let data = log
+            .transactions()
+            .filter(|transaction| matches!(transaction, Transaction::SpiWrite { dc: true, .. }));
+        assert!(data.eq([Transaction::SpiWrite {
+            dc: true,
+            cs: false,
+            bytes: &[0x01]
+        }]));
+    }
+
+    /// Counts how often the pin is pulled low
+    struct CountingPin<'a>(&'a Cell<u32>);
+
+    impl OutputPin for CountingPin<'_> {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            self.0.set(self.0.get() + 1);
+
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn init_resets_display() {
+        let resets = Cell::new(0);
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_reset(CountingPin(&resets), DelayStub)
+            .with_reset_timing(5, 1)
+            .connect_i2c(I2cStub)
+            .into();
+
+        assert_eq!(resets.get(), 0);
+
+        display.init().unwrap();
+        display.init().unwrap();
+        assert_eq!(resets.get(), 2);
+    }
+
+    #[test]
+    fn release_returns_reset_pin() {
+        let resets = Cell::new(0);
+        let display: GraphicsMode<_> = Builder::new()
+            .with_reset(CountingPin(&resets), DelayStub)
+            .connect_i2c(I2cStub)
+            .into();
+
+        let (iface, reset) = display.release().release().release();
+        let (I2cStub, (mut rst, DelayStub)) = (iface.release().release(), reset.release());
+
+        rst.set_low().unwrap();
+        assert_eq!(resets.get(), 1);
+    }
+
+    /// A reset pin that always fails, like a pin on a disconnected port expander
+    struct FailingPin;
+
+    impl OutputPin for FailingPin {
+        type Error = ();
+
+        fn set_low(&mut self) -> Result<(), ()> {
+            Err(())
+        }
+
+        fn set_high(&mut self) -> Result<(), ()> {
+            Err(())
+        }
+    }
+
+    #[test]
+    fn reset_pin_may_fail_with_i2c() {
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_reset(NoOutputPin::<()>::new(), DelayStub)
+            .connect_i2c(I2cStub)
+            .into();
+
+        display.init().unwrap();
+
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_reset(FailingPin, DelayStub)
+            .connect_i2c(I2cStub)
+            .into();
+
+        assert!(matches!(display.init(), Err(Error::Pin(()))));
+    }
+
+    #[test]
+    fn manual_reset_accepts_any_pin() {
+        let recorder = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new().connect_i2c(I2cStub).into();
+
+        display
+            .reset(&mut recorder.pin(PinId::Reset), &mut recorder.delay())
+            .unwrap();
+        assert!(recorder.log().transactions().eq([
+            Transaction::Pin {
+                pin: PinId::Reset,
+                high: true
+            },
+            Transaction::Delay { ms: 1 },
+            Transaction::Pin {
+                pin: PinId::Reset,
+                high: false
+            },
+            Transaction::Delay { ms: 10 },
+            Transaction::Pin {
+                pin: PinId::Reset,
+                high: true
+            },
+        ]));
+
+        assert!(matches!(
+            display.reset(&mut FailingPin, &mut DelayStub),
+            Err(Error::Pin(()))
+        ));
+    }
+
+    #[test]
+    fn hal1_reset_pulses_before_init() {
+        let recorder = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_reset_hal1(recorder.pin(PinId::Reset), recorder.delay())
+            .with_reset_timing(5, 2)
+            .connect_i2c_device(recorder.i2c())
+            .into();
+
+        display.init().unwrap();
+        assert!(recorder.log().transactions().take(7).eq([
+            Transaction::Pin {
+                pin: PinId::Reset,
+                high: true
+            },
+            Transaction::Delay { ms: 1 },
+            Transaction::Pin {
+                pin: PinId::Reset,
+                high: false
+            },
+            Transaction::Delay { ms: 5 },
+            Transaction::Pin {
+                pin: PinId::Reset,
+                high: true
+            },
+            Transaction::Delay { ms: 2 },
+            Transaction::I2cWrite {
+                addr: 0x3c,
+                bytes: &[0x00, 0xAE]
+            },
+        ]));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn hal1_reset_matches_blocking_when_async() {
+        use crate::test_helpers::block_on;
+
+        let blocking = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_reset_hal1(blocking.pin(PinId::Reset), blocking.delay())
+            .connect_i2c_device(blocking.i2c())
+            .into();
+        display.init().unwrap();
+
+        let asynchronous = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_reset_hal1(asynchronous.pin(PinId::Reset), asynchronous.delay())
+            .connect_i2c_async(asynchronous.i2c())
+            .into();
+        block_on(display.init_async()).unwrap();
+
+        assert!(blocking
+            .log()
+            .transactions()
+            .eq(asynchronous.log().transactions()));
+    }
 }
@@ -1,10 +1,27 @@
-use super::interface::DisplayInterface;
+//! sh1106 Commands
+//!
+//! The full SH1106 instruction set as a typed [`Command`] enum. Commands can be sent to the display
+//! from any mode with `send_command`, for example to tune a panel beyond what the driver configures
+//! itself:
+//!
+//! ```rust
+//! # use sh1106::test_helpers::I2cStub;
+//! use sh1106::{command::{Command, PumpVoltage}, prelude::*, Builder};
+//!
+//! let mut disp: GraphicsMode<_> = Builder::new().connect_i2c(I2cStub).into();
+//!
+//! disp.send_command(Command::PumpVoltage(PumpVoltage::V90)).unwrap();
+//!
+//! // Arguments outside of the range accepted by the display are rejected
+//! assert!(disp.send_command(Command::StartLine(64)).is_err());
+//! ```
 
-/// sh1106 Commands
+#[cfg(feature = "async")]
+use super::interface::AsyncDisplayInterface;
+use super::interface::DisplayInterface;
 
 /// Commands
-#[derive(Debug)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Command {
     /// Set contrast. Higher number is higher contrast. Default = 0x7F
     Contrast(u8),
@@ -17,7 +34,7 @@ pub enum Command {
     DisplayOn(bool),
     /// Set column address lower 4 bits
     ColumnAddressLow(u8),
-    /// Set column address higher 4 bits
+    /// Set column address higher 4 bits, from 0-8
     ColumnAddressHigh(u8),
     /// Set page address
     PageAddress(Page),
@@ -25,38 +42,80 @@ pub enum Command {
     StartLine(u8),
     /// Reverse columns from 127-0
     SegmentRemap(bool),
-    /// Set multipex ratio from 15-63 (MUX-1)
+    /// Set multipex ratio from 0-63 (MUX-1)
     Multiplex(u8),
     /// Scan from COM[n-1] to COM0 (where N is mux ratio)
     ReverseComDir(bool),
-    /// Set vertical shift
+    /// Set vertical shift from 0-63
     DisplayOffset(u8),
     /// Setup com hardware configuration
     /// First value indicates sequential (false) or alternative (true)
     /// pin configuration.
     ComPinConfig(bool),
     /// Set up display clock.
-    /// First value is oscillator frequency, increasing with higher value
-    /// Second value is divide ratio - 1
+    /// First value is oscillator frequency, increasing with higher value, from 0-15
+    /// Second value is divide ratio - 1, from 0-15
     DisplayClockDiv(u8, u8),
-    /// Set up phase 1 and 2 of precharge period. each value is from 0-63
+    /// Set up phase 1 and 2 of precharge period in display clocks. Each value is from 1-15
     PreChargePeriod(u8, u8),
     /// Set Vcomh Deselect level
     VcomhDeselect(VcomhLevel),
     /// NOOP
     Noop,
-    /// Enable charge pump
+    /// Turn the internal DC-DC converter (charge pump) on or off. Only takes effect while the
+    /// display is off.
     ChargePump(bool),
+    /// Set the output voltage of the charge pump
+    PumpVoltage(PumpVoltage),
+    /// Start read-modify-write mode. The column address is only incremented by data writes, not
+    /// reads, until [`ReadModifyWriteEnd`](Self::ReadModifyWriteEnd).
+    ReadModifyWriteStart,
+    /// End read-modify-write mode, restoring the column address from the start of the mode
+    ReadModifyWriteEnd,
 }
 
 impl Command {
+    /// Check that all arguments of the command are within the range accepted by the display
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Command::ColumnAddressLow(addr) => addr <= 0xF,
+            Command::ColumnAddressHigh(addr) => addr <= 0x8,
+            Command::StartLine(line) => line <= 63,
+            Command::Multiplex(ratio) => ratio <= 63,
+            Command::DisplayOffset(offset) => offset <= 63,
+            Command::DisplayClockDiv(fosc, div) => fosc <= 0xF && div <= 0xF,
+            Command::PreChargePeriod(phase1, phase2) => {
+                (1..=0xF).contains(&phase1) && (1..=0xF).contains(&phase2)
+            }
+            _ => true,
+        }
+    }
+
     /// Send command to sh1106
-    pub fn send<DI>(self, iface: &mut DI) -> Result<(), DI::Error>
+    pub(crate) fn send<DI>(self, iface: &mut DI) -> Result<(), DI::Error>
     where
         DI: DisplayInterface,
     {
-        // Transform command into a fixed size array of 7 u8 and the real length for sending
-        let (data, len) = match self {
+        let (data, len) = self.encode();
+
+        // Send command over the interface
+        iface.send_commands(&data[0..len])
+    }
+
+    /// Send command to sh1106 over an asynchronous interface
+    #[cfg(feature = "async")]
+    pub(crate) async fn send_async<DI>(self, iface: &mut DI) -> Result<(), DI::Error>
+    where
+        DI: AsyncDisplayInterface,
+    {
+        let (data, len) = self.encode();
+
+        iface.send_commands(&data[0..len]).await
+    }
+
+    /// Transform command into a fixed size array of 7 u8 and the real length for sending
+    fn encode(self) -> ([u8; 7], usize) {
+        match self {
             Command::Contrast(val) => ([0x81, val, 0, 0, 0, 0, 0], 2),
             Command::AllOn(on) => ([0xA4 | (on as u8), 0, 0, 0, 0, 0, 0], 1),
             Command::Invert(inv) => ([0xA6 | (inv as u8), 0, 0, 0, 0, 0, 0], 1),
@@ -77,18 +136,18 @@ impl Command {
                 [0xD9, ((0xF & phase2) << 4) | (0xF & phase1), 0, 0, 0, 0, 0],
                 2,
             ),
-            Command::VcomhDeselect(level) => ([0xDB, (level as u8) << 4, 0, 0, 0, 0, 0], 2),
+            Command::VcomhDeselect(level) => ([0xDB, level.value(), 0, 0, 0, 0, 0], 2),
             Command::Noop => ([0xE3, 0, 0, 0, 0, 0, 0], 1),
             Command::ChargePump(en) => ([0xAD, 0x8A | (en as u8), 0, 0, 0, 0, 0], 2),
-        };
-
-        // Send command over the interface
-        iface.send_commands(&data[0..len])
+            Command::PumpVoltage(voltage) => ([0x30 | (voltage as u8), 0, 0, 0, 0, 0, 0], 1),
+            Command::ReadModifyWriteStart => ([0xE0, 0, 0, 0, 0, 0, 0], 1),
+            Command::ReadModifyWriteEnd => ([0xEE, 0, 0, 0, 0, 0, 0], 1),
+        }
     }
 }
 
 /// Display page
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Page {
     /// Page 0
     Page0 = 0,
@@ -124,38 +183,92 @@ impl From<u8> for Page {
     }
 }
 
-/// Frame interval
-#[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
-pub enum NFrames {
-    /// 2 Frames
-    F2 = 0b111,
-    /// 3 Frames
-    F3 = 0b100,
-    /// 4 Frames
-    F4 = 0b101,
-    /// 5 Frames
-    F5 = 0b000,
-    /// 25 Frames
-    F25 = 0b110,
-    /// 64 Frames
-    F64 = 0b001,
-    /// 128 Frames
-    F128 = 0b010,
-    /// 256 Frames
-    F256 = 0b011,
-}
-
 /// Vcomh Deselect level
-#[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VcomhLevel {
     /// 0.65 * Vcc
-    V065 = 0b001,
+    V065,
     /// 0.77 * Vcc
-    V077 = 0b010,
+    V077,
     /// 0.83 * Vcc
-    V083 = 0b011,
+    V083,
     /// Auto
-    Auto = 0b100,
+    Auto,
+    /// Any level supported by the display, from 0x00 to 0xFF. The deselect voltage is
+    /// `0.430 + level * 0.006415` times Vref, 0x35 after reset.
+    Custom(u8),
+}
+
+impl VcomhLevel {
+    /// Argument byte of the VCOMH deselect command
+    fn value(self) -> u8 {
+        match self {
+            VcomhLevel::V065 => 0x10,
+            VcomhLevel::V077 => 0x20,
+            VcomhLevel::V083 => 0x30,
+            VcomhLevel::Auto => 0x40,
+            VcomhLevel::Custom(level) => level,
+        }
+    }
+}
+
+/// Charge pump output voltage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PumpVoltage {
+    /// 6.4V
+    V64 = 0b00,
+    /// 7.4V
+    V74 = 0b01,
+    /// 8.0V, the default after reset
+    V80 = 0b10,
+    /// 9.0V
+    V90 = 0b11,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(command: Command) -> ([u8; 7], usize) {
+        command.encode()
+    }
+
+    #[test]
+    fn encodes_extended_instructions() {
+        assert_eq!(bytes(Command::PumpVoltage(PumpVoltage::V64)).0[0], 0x30);
+        assert_eq!(bytes(Command::PumpVoltage(PumpVoltage::V90)).0[0], 0x33);
+        assert_eq!(
+            bytes(Command::ReadModifyWriteStart),
+            ([0xE0, 0, 0, 0, 0, 0, 0], 1)
+        );
+        assert_eq!(
+            bytes(Command::ReadModifyWriteEnd),
+            ([0xEE, 0, 0, 0, 0, 0, 0], 1)
+        );
+        assert_eq!(bytes(Command::ChargePump(false)).0[..2], [0xAD, 0x8A]);
+        assert_eq!(bytes(Command::ChargePump(true)).0[..2], [0xAD, 0x8B]);
+        assert_eq!(
+            bytes(Command::VcomhDeselect(VcomhLevel::Auto)).0[..2],
+            [0xDB, 0x40]
+        );
+        assert_eq!(
+            bytes(Command::VcomhDeselect(VcomhLevel::Custom(0x35))).0[..2],
+            [0xDB, 0x35]
+        );
+    }
+
+    #[test]
+    fn validates_argument_ranges() {
+        assert!(Command::StartLine(63).is_valid());
+        assert!(!Command::StartLine(64).is_valid());
+        assert!(Command::ColumnAddressHigh(8).is_valid());
+        assert!(!Command::ColumnAddressHigh(9).is_valid());
+        assert!(!Command::ColumnAddressLow(16).is_valid());
+        assert!(!Command::Multiplex(64).is_valid());
+        assert!(!Command::DisplayOffset(64).is_valid());
+        assert!(!Command::DisplayClockDiv(16, 0).is_valid());
+        assert!(Command::PreChargePeriod(1, 15).is_valid());
+        assert!(!Command::PreChargePeriod(0, 15).is_valid());
+        assert!(Command::Contrast(0xFF).is_valid());
+    }
 }
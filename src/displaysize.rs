@@ -1,7 +1,7 @@
 //! Display size
 
 /// Display size enumeration
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DisplaySize {
     /// 128 by 64 pixels
     Display128x64,
@@ -11,6 +11,8 @@ pub enum DisplaySize {
     Display128x32,
     /// 132 by 64 pixels
     Display132x64,
+    /// Any other panel geometry
+    Custom(CustomSize),
 }
 
 impl DisplaySize {
@@ -21,6 +23,7 @@ impl DisplaySize {
             DisplaySize::Display128x64NoOffset => (128, 64),
             DisplaySize::Display128x32 => (128, 32),
             DisplaySize::Display132x64 => (132, 64),
+            DisplaySize::Custom(size) => (size.width, size.height),
         }
     }
 
@@ -31,6 +34,227 @@ impl DisplaySize {
             DisplaySize::Display128x64NoOffset => 0,
             DisplaySize::Display128x32 => 2,
             DisplaySize::Display132x64 => 0,
+            DisplaySize::Custom(size) => size.column_offset,
         }
     }
+
+    /// Get the vertical shift of the display in COM lines, as set with the display offset (0xD3)
+    /// command
+    pub fn display_offset(self) -> u8 {
+        match self {
+            DisplaySize::Custom(size) => size.display_offset,
+            _ => 0,
+        }
+    }
+
+    /// Whether the panel uses the alternative (`true`) or sequential (`false`) COM pin
+    /// configuration
+    pub fn alternative_com_pins(self) -> bool {
+        match self {
+            DisplaySize::Display128x32 => false,
+            DisplaySize::Display128x64
+            | DisplaySize::Display128x64NoOffset
+            | DisplaySize::Display132x64 => true,
+            DisplaySize::Custom(size) => size.alternative_com_pins,
+        }
+    }
+}
+
+/// Geometry of a panel not covered by the predefined [`DisplaySize`] variants
+///
+/// ```rust
+/// use sh1106::{displaysize::{CustomSize, DisplaySize}, Builder};
+///
+/// // 72x40 glass, starting at RAM column 30 and shifted down by 12 COM lines
+/// let size = CustomSize::new(72, 40)
+///     .with_column_offset(30)
+///     .with_display_offset(12);
+///
+/// Builder::new().with_size(DisplaySize::Custom(size));
+/// ```
+///
+/// [`GraphicsMode`](crate::mode::GraphicsMode) uses a buffer large enough for the biggest display
+/// by default. To size it exactly, implement [`PanelSize`] for a type of your own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CustomSize {
+    width: u8,
+    height: u8,
+    column_offset: u8,
+    display_offset: u8,
+    alternative_com_pins: bool,
+}
+
+impl CustomSize {
+    /// Create a panel geometry of `width` by `height` pixels with no offsets. The COM pin
+    /// configuration defaults to alternative for panels taller than 32 pixels, and sequential
+    /// otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero or larger than 132, or if `height` is not a multiple of 8
+    /// between 16 and 64.
+    pub const fn new(width: u8, height: u8) -> Self {
+        assert!(width > 0 && width <= 132, "width must be between 1 and 132");
+        assert!(
+            height >= 16 && height <= 64 && height & 0x7 == 0,
+            "height must be a multiple of 8 between 16 and 64"
+        );
+
+        Self {
+            width,
+            height,
+            column_offset: 0,
+            display_offset: 0,
+            alternative_com_pins: height > 32,
+        }
+    }
+
+    /// Set the first RAM column shown on the panel
+    ///
+    /// # Panics
+    ///
+    /// Panics if the panel would extend past the 132 columns of display RAM.
+    pub const fn with_column_offset(self, column_offset: u8) -> Self {
+        assert!(
+            self.width as u16 + column_offset as u16 <= 132,
+            "panel must fit into 132 columns"
+        );
+
+        Self {
+            column_offset,
+            ..self
+        }
+    }
+
+    /// Set the vertical shift in COM lines sent with the display offset (0xD3) command
+    ///
+    /// # Panics
+    ///
+    /// Panics if `display_offset` is larger than 63.
+    pub const fn with_display_offset(self, display_offset: u8) -> Self {
+        assert!(
+            display_offset <= 63,
+            "display offset must be between 0 and 63"
+        );
+
+        Self {
+            display_offset,
+            ..self
+        }
+    }
+
+    /// Use the alternative (`true`) or sequential (`false`) COM pin configuration
+    pub const fn with_alternative_com_pins(self, alternative_com_pins: bool) -> Self {
+        Self {
+            alternative_com_pins,
+            ..self
+        }
+    }
+}
+
+/// Panel size known at compile time, used to allocate a framebuffer of exactly the right size in
+/// [`GraphicsMode`](crate::mode::GraphicsMode).
+///
+/// The default, [`DynamicSize`], allocates a buffer large enough for any [`DisplaySize`] and uses
+/// the size configured with [`Builder::with_size`](crate::Builder::with_size). The other
+/// implementations replace the configured size with their own:
+///
+/// ```rust
+/// use sh1106::{displaysize::Size128x32, prelude::*, Builder};
+/// # let i2c = sh1106::test_helpers::I2cStub;
+///
+/// // Uses a 512 byte buffer instead of 1056 bytes
+/// let display: GraphicsMode<_, Size128x32> = Builder::new().connect_i2c(i2c).into();
+///
+/// assert_eq!(display.get_dimensions(), (128, 32));
+/// ```
+pub trait PanelSize {
+    /// Framebuffer storage
+    type Buffer: AsRef<[u8]> + AsMut<[u8]>;
+
+    /// Create a new, zeroed framebuffer
+    fn buffer() -> Self::Buffer;
+
+    /// Display size to use, given the size configured in the builder
+    fn display_size(configured: DisplaySize) -> DisplaySize;
+}
+
+/// Use the display size configured with [`Builder::with_size`](crate::Builder::with_size),
+/// allocating a buffer large enough for the biggest supported display.
+#[derive(Debug, Clone, Copy)]
+pub struct DynamicSize;
+
+impl PanelSize for DynamicSize {
+    type Buffer = [u8; 132 * 64 / 8];
+
+    fn buffer() -> Self::Buffer {
+        [0; 132 * 64 / 8]
+    }
+
+    fn display_size(configured: DisplaySize) -> DisplaySize {
+        configured
+    }
+}
+
+macro_rules! panel_size {
+    ($name:ident, $size:ident, $width:expr, $height:expr, $offset:expr) => {
+        #[doc = concat!("Fixed [`DisplaySize::", stringify!($size), "`] panel")]
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl $name {
+            /// Width of the panel in pixels
+            pub const WIDTH: u8 = $width;
+            /// Height of the panel in pixels
+            pub const HEIGHT: u8 = $height;
+            /// Column offset of the panel in display RAM
+            pub const COLUMN_OFFSET: u8 = $offset;
+        }
+
+        impl PanelSize for $name {
+            type Buffer = [u8; $width * $height / 8];
+
+            fn buffer() -> Self::Buffer {
+                [0; $width * $height / 8]
+            }
+
+            fn display_size(_configured: DisplaySize) -> DisplaySize {
+                DisplaySize::$size
+            }
+        }
+    };
+}
+
+panel_size!(Size128x64, Display128x64, 128, 64, 2);
+panel_size!(Size128x64NoOffset, Display128x64NoOffset, 128, 64, 0);
+panel_size!(Size128x32, Display128x32, 128, 32, 2);
+panel_size!(Size132x64, Display132x64, 132, 64, 0);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_buffers_match_dimensions() {
+        fn check<S: PanelSize>() {
+            let (w, h) = S::display_size(DisplaySize::Display132x64).dimensions();
+
+            assert_eq!(S::buffer().as_ref().len(), w as usize * h as usize / 8);
+        }
+
+        assert_eq!(
+            (Size128x32::WIDTH, Size128x32::HEIGHT),
+            DisplaySize::Display128x32.dimensions()
+        );
+        assert_eq!(
+            Size128x32::COLUMN_OFFSET,
+            DisplaySize::Display128x32.column_offset()
+        );
+
+        check::<Size128x64>();
+        check::<Size128x64NoOffset>();
+        check::<Size128x32>();
+        check::<Size132x64>();
+        check::<DynamicSize>();
+    }
 }
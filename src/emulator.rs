@@ -0,0 +1,544 @@
+//! Host-side SH1106 emulator
+//!
+//! [`Emulator`] implements [`DisplayInterface`] by decoding the command stream the way an SH1106
+//! would and writing data into a modelled 132x64 display RAM. The image a panel of the given size
+//! would show can then be checked in tests running without hardware:
+//!
+//! ```rust
+//! use sh1106::{emulator::Emulator, prelude::*, Builder};
+//!
+//! let mut emulator = Emulator::new(DisplaySize::Display128x64);
+//! let mut display: GraphicsMode<_> = Builder::new().connect_interface(&mut emulator).into();
+//!
+//! display.init().unwrap();
+//! display.set_pixel(3, 5, 1);
+//! display.flush().unwrap();
+//!
+//! assert!(emulator.pixel(3, 5));
+//! assert!(!emulator.pixel(5, 3));
+//! ```
+//!
+//! The panel is modelled as wired to an SH1106 in the way the driver expects: with segment remap
+//! and reversed COM scan direction, i.e. [`DisplayRotation::Rotate0`], the first visible column and
+//! RAM row are shown in the top left corner.
+//!
+//! Only available with the `std` feature enabled.
+//!
+//! [`DisplayRotation::Rotate0`]: crate::displayrotation::DisplayRotation::Rotate0
+
+use core::{convert::Infallible, fmt};
+use std::string::{String, ToString};
+
+use crate::{displaysize::DisplaySize, interface::DisplayInterface, snapshot::Image, Error};
+
+/// Number of columns in display RAM
+const RAM_COLUMNS: usize = 132;
+
+/// Number of pages in display RAM
+const RAM_PAGES: usize = 8;
+
+/// Number of rows in display RAM
+const RAM_ROWS: usize = RAM_PAGES * 8;
+
+/// Emulated SH1106 controller and panel
+#[derive(Clone)]
+pub struct Emulator {
+    display_size: DisplaySize,
+    ram: [[u8; RAM_COLUMNS]; RAM_PAGES],
+    page: u8,
+    column: u8,
+    /// Column to return to at the end of read-modify-write mode
+    rmw_column: Option<u8>,
+    start_line: u8,
+    display_offset: u8,
+    multiplex: u8,
+    segment_remap: bool,
+    reverse_com_dir: bool,
+    alternative_com_pins: bool,
+    contrast: u8,
+    invert: bool,
+    all_on: bool,
+    display_on: bool,
+    charge_pump: bool,
+    /// First byte of a two byte command waiting for its argument
+    pending: Option<u8>,
+}
+
+impl Emulator {
+    /// Create an emulator for a panel of the given size, in the state of the SH1106 after reset
+    pub fn new(display_size: DisplaySize) -> Self {
+        Self {
+            display_size,
+            ram: [[0; RAM_COLUMNS]; RAM_PAGES],
+            page: 0,
+            column: 0,
+            rmw_column: None,
+            start_line: 0,
+            display_offset: 0,
+            multiplex: 63,
+            segment_remap: false,
+            reverse_com_dir: false,
+            alternative_com_pins: true,
+            contrast: 0x80,
+            invert: false,
+            all_on: false,
+            display_on: false,
+            charge_pump: true,
+            pending: None,
+        }
+    }
+
+    /// Width and height of the emulated panel
+    pub fn dimensions(&self) -> (u8, u8) {
+        self.display_size.dimensions()
+    }
+
+    /// Whether the pixel at the given panel coordinates is lit. Pixels outside of the panel are
+    /// never lit.
+    pub fn pixel(&self, x: u32, y: u32) -> bool {
+        let (width, height) = self.dimensions();
+
+        if x >= width as u32 || y >= height as u32 || !self.display_on {
+            return false;
+        }
+
+        if self.all_on {
+            return true;
+        }
+
+        match self.ram_position(x as usize, y as usize) {
+            Some((column, row)) => {
+                let lit = self.ram[row / 8][column] & (1 << (row % 8)) != 0;
+
+                lit != self.invert
+            }
+            None => false,
+        }
+    }
+
+    /// Render the visible image as text, one line per row with `#` for lit and `.` for unlit
+    /// pixels
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+
+    /// Take a snapshot of the visible image, e.g. to compare it against a golden image
+    pub fn snapshot(&self) -> Image {
+        let (width, height) = self.dimensions();
+
+        Image::from_fn(width as u32, height as u32, |x, y| self.pixel(x, y))
+    }
+
+    /// Display RAM, as 8 pages of 132 columns
+    pub fn ram(&self) -> &[[u8; RAM_COLUMNS]; RAM_PAGES] {
+        &self.ram
+    }
+
+    /// Current page and column address
+    pub fn address(&self) -> (u8, u8) {
+        (self.page, self.column)
+    }
+
+    /// Display RAM row shown on the first scanned line
+    pub fn start_line(&self) -> u8 {
+        self.start_line
+    }
+
+    /// Current contrast setting
+    pub fn contrast(&self) -> u8 {
+        self.contrast
+    }
+
+    /// Whether the display is turned on
+    pub fn is_display_on(&self) -> bool {
+        self.display_on
+    }
+
+    /// Whether the internal charge pump is enabled
+    pub fn is_charge_pump_on(&self) -> bool {
+        self.charge_pump
+    }
+
+    /// Whether the COM pins are configured as alternative (`true`) or sequential (`false`)
+    pub fn alternative_com_pins(&self) -> bool {
+        self.alternative_com_pins
+    }
+
+    /// Display RAM column and row shown at the given panel coordinates, or `None` if the panel
+    /// row isn't scanned with the current multiplex ratio
+    fn ram_position(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let column_offset = self.display_size.column_offset() as usize;
+        let lines = self.multiplex as usize + 1;
+
+        // Segment driving panel column `x`. Remapping reverses the order of RAM columns on the
+        // segments.
+        let segment = RAM_COLUMNS - 1 - column_offset - x;
+        let column = if self.segment_remap {
+            RAM_COLUMNS - 1 - segment
+        } else {
+            segment
+        };
+
+        // Scan step driving panel row `y`. Reversing the COM scan direction scans the panel from
+        // the top.
+        let step = if self.reverse_com_dir {
+            y
+        } else {
+            lines.checked_sub(y + 1)?
+        };
+
+        if step >= lines {
+            return None;
+        }
+
+        // The display offset moves the start line to a later scan step
+        let line = (step + lines - self.display_offset as usize % lines) % lines;
+        let row = (self.start_line as usize + line) % RAM_ROWS;
+
+        Some((column, row))
+    }
+
+    fn command(&mut self, byte: u8) {
+        if let Some(command) = self.pending.take() {
+            return self.argument(command, byte);
+        }
+
+        match byte {
+            0x00..=0x0F => self.column = (self.column & 0xF0) | byte,
+            0x10..=0x1F => self.column = (self.column & 0x0F) | ((byte & 0x0F) << 4),
+            0x30..=0x33 => {}
+            0x40..=0x7F => self.start_line = byte & 0x3F,
+            0xA0 | 0xA1 => self.segment_remap = byte & 1 != 0,
+            0xA4 | 0xA5 => self.all_on = byte & 1 != 0,
+            0xA6 | 0xA7 => self.invert = byte & 1 != 0,
+            0xAE | 0xAF => self.display_on = byte & 1 != 0,
+            0xB0..=0xB7 => self.page = byte & 0x07,
+            0xC0..=0xCF => self.reverse_com_dir = byte & 0x08 != 0,
+            0xE0 => self.rmw_column = Some(self.column),
+            0xEE => {
+                if let Some(column) = self.rmw_column.take() {
+                    self.column = column;
+                }
+            }
+            0x81 | 0xA8 | 0xAD | 0xD3 | 0xD5 | 0xD9 | 0xDA | 0xDB => self.pending = Some(byte),
+            // NOP and anything else the emulator doesn't model
+            _ => {}
+        }
+    }
+
+    fn argument(&mut self, command: u8, byte: u8) {
+        match command {
+            0x81 => self.contrast = byte,
+            0xA8 => self.multiplex = byte & 0x3F,
+            0xAD => self.charge_pump = byte & 1 != 0,
+            0xD3 => self.display_offset = byte & 0x3F,
+            0xDA => self.alternative_com_pins = byte & 0x10 != 0,
+            // Clock, precharge and VCOMH settings don't affect the image
+            _ => {}
+        }
+    }
+
+    fn data(&mut self, byte: u8) {
+        if (self.column as usize) < RAM_COLUMNS {
+            self.ram[self.page as usize][self.column as usize] = byte;
+            self.column += 1;
+        }
+    }
+}
+
+impl fmt::Display for Emulator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (width, height) = self.dimensions();
+
+        for y in 0..height as u32 {
+            for x in 0..width as u32 {
+                f.write_str(if self.pixel(x, y) { "#" } else { "." })?;
+            }
+
+            f.write_str("\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl DisplayInterface for Emulator {
+    type Error = Error<Infallible, Infallible>;
+
+    fn init(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
+        for &byte in cmds {
+            self.command(byte);
+        }
+
+        Ok(())
+    }
+
+    fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        for &byte in buf {
+            self.data(byte);
+        }
+
+        Ok(())
+    }
+}
+
+impl DisplayInterface for &mut Emulator {
+    type Error = Error<Infallible, Infallible>;
+
+    fn init(&mut self) -> Result<(), Self::Error> {
+        (**self).init()
+    }
+
+    fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
+        (**self).send_commands(cmds)
+    }
+
+    fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        (**self).send_data(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::Builder, command::Command, displayrotation::DisplayRotation,
+        displaysize::CustomSize, mode::GraphicsMode,
+    };
+
+    #[test]
+    fn init_configures_controller() {
+        let mut emulator = Emulator::new(DisplaySize::Display128x64);
+        let mut display: GraphicsMode<_> = Builder::new().connect_interface(&mut emulator).into();
+
+        display.init().unwrap();
+
+        assert!(emulator.is_display_on());
+        assert!(emulator.is_charge_pump_on());
+        assert_eq!(emulator.contrast(), 0x80);
+        assert_eq!(emulator.start_line(), 0);
+        assert!(emulator.render().chars().all(|c| c == '.' || c == '\n'));
+    }
+
+    #[test]
+    fn rotate180_shows_rotated_image() {
+        let mut emulator = Emulator::new(DisplaySize::Display128x64);
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_rotation(DisplayRotation::Rotate180)
+            .connect_interface(&mut emulator)
+            .into();
+
+        display.init().unwrap();
+        display.set_pixel(0, 0, 1);
+        display.flush().unwrap();
+
+        assert!(emulator.pixel(127, 63));
+        assert!(!emulator.pixel(0, 0));
+    }
+
+    #[test]
+    fn rotations_match_physical_orientation() {
+        let sizes = [
+            DisplaySize::Display128x64,
+            DisplaySize::Display128x64NoOffset,
+            DisplaySize::Display128x32,
+            DisplaySize::Display132x64,
+            DisplaySize::Custom(CustomSize::new(96, 16).with_column_offset(30)),
+        ];
+        let rotations = [
+            DisplayRotation::Rotate0,
+            DisplayRotation::Rotate90,
+            DisplayRotation::Rotate180,
+            DisplayRotation::Rotate270,
+        ];
+
+        for size in sizes {
+            for rotation in rotations {
+                let mut emulator = Emulator::new(size);
+                let mut display: GraphicsMode<_> = Builder::new()
+                    .with_size(size)
+                    .with_rotation(rotation)
+                    .connect_interface(&mut emulator)
+                    .into();
+
+                display.init().unwrap();
+                display.scroll(3).unwrap();
+                display.set_pixel(1, 2, 1);
+                display.set_pixel(9, 0, 1);
+                display.flush().unwrap();
+
+                // Where the viewer's pixels end up on the unrotated panel
+                let (width, height) = size.dimensions();
+                let (w, h) = (width as u32, height as u32);
+                let panel = |x: u32, y: u32| match rotation {
+                    DisplayRotation::Rotate0 => (x, y),
+                    DisplayRotation::Rotate90 => (w - 1 - y, x),
+                    DisplayRotation::Rotate180 => (w - 1 - x, h - 1 - y),
+                    DisplayRotation::Rotate270 => (y, h - 1 - x),
+                };
+                let expected = Image::from_fn(w, h, |px, py| {
+                    [(1, 2), (9, 0)]
+                        .iter()
+                        .any(|&(x, y)| panel(x, y) == (px, py))
+                });
+
+                assert_eq!(
+                    emulator.snapshot().compare_image(&expected),
+                    Ok(()),
+                    "{:?} rotated {:?}",
+                    size.dimensions(),
+                    rotation
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn flips_mirror_rotated_image() {
+        let size = DisplaySize::Custom(CustomSize::new(96, 32).with_column_offset(30));
+        let rotations = [
+            DisplayRotation::Rotate0,
+            DisplayRotation::Rotate90,
+            DisplayRotation::Rotate180,
+            DisplayRotation::Rotate270,
+        ];
+
+        for rotation in rotations {
+            for (flip_x, flip_y) in [(true, false), (false, true), (true, true)] {
+                let mut unflipped = Emulator::new(size);
+                let mut flipped = Emulator::new(size);
+
+                for (emulator, flip) in [
+                    (&mut unflipped, (false, false)),
+                    (&mut flipped, (flip_x, flip_y)),
+                ] {
+                    let mut display: GraphicsMode<_> = Builder::new()
+                        .with_size(size)
+                        .with_rotation(rotation)
+                        .with_flip(flip.0, flip.1)
+                        .connect_interface(emulator)
+                        .into();
+
+                    display.init().unwrap();
+                    display.set_pixel(1, 2, 1);
+                    display.set_pixel(9, 0, 1);
+                    display.flush().unwrap();
+                }
+
+                // Flipping the viewer's axes of the unflipped image
+                let (width, height) = size.dimensions();
+                let (w, h) = (width as u32, height as u32);
+                let mirror = |x: u32, y: u32| match rotation {
+                    DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (
+                        if flip_x { w - 1 - x } else { x },
+                        if flip_y { h - 1 - y } else { y },
+                    ),
+                    DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (
+                        if flip_y { w - 1 - x } else { x },
+                        if flip_x { h - 1 - y } else { y },
+                    ),
+                };
+                let expected = Image::from_fn(w, h, |x, y| {
+                    let (x, y) = mirror(x, y);
+                    unflipped.pixel(x, y)
+                });
+
+                assert_eq!(
+                    flipped.snapshot().compare_image(&expected),
+                    Ok(()),
+                    "{:?} flipped {:?}",
+                    rotation,
+                    (flip_x, flip_y)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn changing_rotation_resends_buffer() {
+        let mut emulator = Emulator::new(DisplaySize::Display128x64NoOffset);
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_size(DisplaySize::Display128x64NoOffset)
+            .connect_interface(&mut emulator)
+            .into();
+
+        display.init().unwrap();
+        display.set_pixel(0, 0, 1);
+        display.flush().unwrap();
+        display.set_rotation(DisplayRotation::Rotate180).unwrap();
+        display.flush().unwrap();
+
+        assert!(emulator.pixel(127, 63));
+        assert_eq!(emulator.snapshot().to_string().matches('#').count(), 1);
+    }
+
+    #[test]
+    fn start_line_scrolls_image() {
+        let mut emulator = Emulator::new(DisplaySize::Display128x32);
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_size(DisplaySize::Display128x32)
+            .connect_interface(&mut emulator)
+            .into();
+
+        display.init().unwrap();
+        display.set_pixel(10, 4, 1);
+        display.flush().unwrap();
+        display.scroll(4).unwrap();
+        display.set_pixel(20, 31, 1);
+        display.flush().unwrap();
+
+        assert!(emulator.pixel(10, 0));
+        assert!(emulator.pixel(20, 31));
+        assert_eq!(emulator.ram()[4][22], 1 << 3);
+    }
+
+    #[test]
+    fn display_control_commands_affect_image() {
+        let mut emulator = Emulator::new(DisplaySize::Display128x64);
+        let mut display: GraphicsMode<_> = Builder::new().connect_interface(&mut emulator).into();
+
+        display.init().unwrap();
+        display.send_command(Command::Invert(true)).unwrap();
+        assert!(emulator.pixel(0, 0));
+
+        emulator.send_commands(&[0xA6, 0xA5]).unwrap();
+        assert!(emulator.pixel(64, 32));
+
+        emulator.send_commands(&[0xAE]).unwrap();
+        assert!(!emulator.pixel(64, 32));
+    }
+
+    #[test]
+    fn snapshot_matches_buffer() {
+        let mut emulator = Emulator::new(DisplaySize::Display128x32);
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_size(DisplaySize::Display128x32)
+            .connect_interface(&mut emulator)
+            .into();
+
+        display.init().unwrap();
+        display.set_pixel(0, 0, 1);
+        display.set_pixel(100, 30, 1);
+        display.flush().unwrap();
+        let expected = display.to_png();
+
+        assert_eq!(emulator.snapshot().compare(&expected), Ok(()));
+    }
+
+    #[test]
+    fn read_modify_write_restores_column() {
+        let mut emulator = Emulator::new(DisplaySize::Display128x64);
+
+        emulator.send_commands(&[0xB2, 0x04, 0x11, 0xE0]).unwrap();
+        emulator.send_data(&[0xFF, 0xFF]).unwrap();
+        assert_eq!(emulator.address(), (2, 0x16));
+
+        emulator.send_commands(&[0xEE]).unwrap();
+        assert_eq!(emulator.address(), (2, 0x14));
+        assert_eq!(emulator.ram()[2][0x14..0x16], [0xFF, 0xFF]);
+    }
+}
@@ -0,0 +1,118 @@
+//! Initialisation sequence parameters
+//!
+//! Panel vendors often specify their own clock, precharge and VCOMH settings, and modules with an
+//! external VCC need the internal charge pump to stay off. An [`InitConfig`] holds every parameter
+//! sent by the `init` method of the display modes and is given to the
+//! [`Builder`](crate::Builder):
+//!
+//! ```rust
+//! use sh1106::{command::VcomhLevel, initconfig::InitConfig, Builder};
+//! # let i2c = sh1106::test_helpers::I2cStub;
+//!
+//! let config = InitConfig::new()
+//!     .with_charge_pump(false)
+//!     .with_precharge_period(0x2, 0x2)
+//!     .with_vcomh_deselect(VcomhLevel::V077)
+//!     .with_contrast(0xCF);
+//!
+//! Builder::new().with_init_config(config).connect_i2c(i2c);
+//! ```
+
+use crate::command::{PumpVoltage, VcomhLevel};
+
+/// Parameters of the initialisation sequence
+///
+/// Setters panic if given values outside of the range accepted by the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InitConfig {
+    pub(crate) oscillator_frequency: u8,
+    pub(crate) clock_divide_ratio: u8,
+    pub(crate) precharge_phase1: u8,
+    pub(crate) precharge_phase2: u8,
+    pub(crate) vcomh_deselect: VcomhLevel,
+    pub(crate) contrast: u8,
+    pub(crate) charge_pump: bool,
+    pub(crate) pump_voltage: PumpVoltage,
+}
+
+impl Default for InitConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InitConfig {
+    /// Create a configuration with the driver's default values: oscillator frequency 0x8, divide
+    /// ratio 1, precharge periods of 1 and 15 clocks, automatic VCOMH deselect level, contrast
+    /// 0x80 and the charge pump on at 8.0V.
+    pub const fn new() -> Self {
+        Self {
+            oscillator_frequency: 0x8,
+            clock_divide_ratio: 0x0,
+            precharge_phase1: 0x1,
+            precharge_phase2: 0xF,
+            vcomh_deselect: VcomhLevel::Auto,
+            contrast: 0x80,
+            charge_pump: true,
+            pump_voltage: PumpVoltage::V80,
+        }
+    }
+
+    /// Set the oscillator frequency, from 0-15, and the clock divide ratio - 1, from 0-15
+    pub const fn with_display_clock(self, oscillator_frequency: u8, divide_ratio: u8) -> Self {
+        assert!(
+            oscillator_frequency <= 0xF && divide_ratio <= 0xF,
+            "clock values must be between 0 and 15"
+        );
+
+        Self {
+            oscillator_frequency,
+            clock_divide_ratio: divide_ratio,
+            ..self
+        }
+    }
+
+    /// Set phase 1 and 2 of the precharge period in display clocks, each from 1-15
+    pub const fn with_precharge_period(self, phase1: u8, phase2: u8) -> Self {
+        assert!(
+            phase1 >= 1 && phase1 <= 0xF && phase2 >= 1 && phase2 <= 0xF,
+            "precharge periods must be between 1 and 15"
+        );
+
+        Self {
+            precharge_phase1: phase1,
+            precharge_phase2: phase2,
+            ..self
+        }
+    }
+
+    /// Set the VCOMH deselect level
+    pub const fn with_vcomh_deselect(self, vcomh_deselect: VcomhLevel) -> Self {
+        Self {
+            vcomh_deselect,
+            ..self
+        }
+    }
+
+    /// Set the initial contrast
+    pub const fn with_contrast(self, contrast: u8) -> Self {
+        Self { contrast, ..self }
+    }
+
+    /// Enable or disable the internal charge pump. Disable it for modules supplying VCC
+    /// externally.
+    pub const fn with_charge_pump(self, charge_pump: bool) -> Self {
+        Self {
+            charge_pump,
+            ..self
+        }
+    }
+
+    /// Set the output voltage of the internal charge pump
+    pub const fn with_pump_voltage(self, pump_voltage: PumpVoltage) -> Self {
+        Self {
+            pump_voltage,
+            ..self
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! Adapter for interfaces from the [`display-interface`](https://crates.io/crates/display-interface)
+//! crate
+
+use core::convert::Infallible;
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+#[cfg(feature = "async")]
+use super::AsyncDisplayInterface;
+use super::DisplayInterface;
+use crate::Error;
+
+/// Display interface wrapping any [`WriteOnlyDataCommand`]
+///
+/// This allows the parallel GPIO, I2C and SPI interfaces from the `display-interface` ecosystem to
+/// be used with this driver. Create one with
+/// [connect](../../builder/struct.Builder.html#method.connect).
+pub struct DataCommandInterface<DI> {
+    iface: DI,
+}
+
+impl<DI> DataCommandInterface<DI> {
+    /// Create new sh1106 interface from a `display-interface` implementation
+    pub fn new(iface: DI) -> Self {
+        Self { iface }
+    }
+
+    /// Release the wrapped `display-interface` implementation
+    pub fn release(self) -> DI {
+        self.iface
+    }
+}
+
+impl<DI> DisplayInterface for DataCommandInterface<DI>
+where
+    DI: WriteOnlyDataCommand,
+{
+    type Error = Error<DisplayError, Infallible>;
+
+    fn init(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
+        self.iface
+            .send_commands(DataFormat::U8(cmds))
+            .map_err(Error::Comm)
+    }
+
+    fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.iface
+            .send_data(DataFormat::U8(buf))
+            .map_err(Error::Comm)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<DI> AsyncDisplayInterface for DataCommandInterface<DI>
+where
+    DI: display_interface::AsyncWriteOnlyDataCommand,
+{
+    type Error = Error<DisplayError, Infallible>;
+
+    async fn init(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
+        self.iface
+            .send_commands(DataFormat::U8(cmds))
+            .await
+            .map_err(Error::Comm)
+    }
+
+    async fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.iface
+            .send_data(DataFormat::U8(buf))
+            .await
+            .map_err(Error::Comm)
+    }
+}
@@ -1,9 +1,17 @@
 //! SH1106 I2C Interface
 
+use core::convert::Infallible;
+
 use hal;
 
+#[cfg(feature = "async")]
+use super::AsyncDisplayInterface;
 use super::DisplayInterface;
-use crate::{command::Page, Error};
+use crate::Error;
+
+/// Maximum number of data bytes sent in a single I2C transaction. The display keeps advancing its
+/// column address across transactions, so longer buffers are simply split up.
+const CHUNKLEN: usize = 128;
 
 /// SH1106 I2C communication interface
 pub struct I2cInterface<I2C> {
@@ -19,67 +27,205 @@ where
     pub fn new(i2c: I2C, addr: u8) -> Self {
         Self { i2c, addr }
     }
+
+    /// Release the I2C peripheral
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
 }
 
 impl<I2C, CommE> DisplayInterface for I2cInterface<I2C>
 where
     I2C: hal::blocking::i2c::Write<Error = CommE>,
 {
-    type Error = Error<CommE, ()>;
+    type Error = Error<CommE, Infallible>;
 
     fn init(&mut self) -> Result<(), Self::Error> {
         Ok(())
     }
 
     fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
-        // Copy over given commands to new aray to prefix with command identifier
-        let mut writebuf: [u8; 8] = [0; 8];
-        writebuf[1..=cmds.len()].copy_from_slice(&cmds);
+        let (writebuf, len) = command_frame(cmds);
 
         self.i2c
-            .write(self.addr, &writebuf[..=cmds.len()])
+            .write(self.addr, &writebuf[..len])
             .map_err(Error::Comm)
     }
 
     fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
-        // Display is always 128px wide
-        const CHUNKLEN: usize = 128;
+        let addr = self.addr;
+        let i2c = &mut self.i2c;
 
-        const BUFLEN: usize = CHUNKLEN + 1;
+        write_data_chunks(buf, |bytes| i2c.write(addr, bytes).map_err(Error::Comm))
+    }
+}
 
-        // Noop if the data buffer is empty
-        if buf.is_empty() {
-            return Ok(());
-        }
+/// SH1106 I2C communication interface for `embedded-hal` 1.0 I2C peripherals
+pub struct I2cDeviceInterface<I2C> {
+    i2c: I2C,
+    addr: u8,
+}
+
+impl<I2C> I2cDeviceInterface<I2C> {
+    /// Create new sh1106 I2C interface
+    pub fn new(i2c: I2C, addr: u8) -> Self {
+        Self { i2c, addr }
+    }
+
+    /// Release the I2C peripheral
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+impl<I2C> DisplayInterface for I2cDeviceInterface<I2C>
+where
+    I2C: embedded_hal_1::i2c::I2c,
+{
+    type Error = Error<I2C::Error, Infallible>;
+
+    fn init(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
+        let (writebuf, len) = command_frame(cmds);
+
+        self.i2c
+            .write(self.addr, &writebuf[..len])
+            .map_err(Error::Comm)
+    }
+
+    fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        let addr = self.addr;
+        let i2c = &mut self.i2c;
 
-        let mut page = Page::Page0 as u8;
+        write_data_chunks(buf, |bytes| i2c.write(addr, bytes).map_err(Error::Comm))
+    }
+}
 
-        // Display width plus 4 start bytes
-        let mut writebuf: [u8; BUFLEN] = [0; BUFLEN];
+#[cfg(feature = "async")]
+impl<I2C> AsyncDisplayInterface for I2cDeviceInterface<I2C>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    type Error = Error<I2C::Error, Infallible>;
 
-        writebuf[0] = 0x40; // Following bytes are data bytes
+    async fn init(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
+        let (writebuf, len) = command_frame(cmds);
+
+        self.i2c
+            .write(self.addr, &writebuf[..len])
+            .await
+            .map_err(Error::Comm)
+    }
+
+    async fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        let mut writebuf = [0; CHUNKLEN + 1];
 
         for chunk in buf.chunks(CHUNKLEN) {
-            // Copy over all data from buffer, leaving the data command byte intact
-            writebuf[1..BUFLEN].copy_from_slice(&chunk);
+            let frame = data_frame(&mut writebuf, chunk);
 
             self.i2c
-                .write(
-                    self.addr,
-                    &[
-                        0x00, // Command
-                        page, // Page address
-                        0x02, // Lower column address
-                        0x10, // Upper column address (always zero, base is 10h)
-                    ],
-                )
+                .write(self.addr, frame)
+                .await
                 .map_err(Error::Comm)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Copy over given commands to new array to prefix with command identifier
+fn command_frame(cmds: &[u8]) -> ([u8; 8], usize) {
+    let mut writebuf: [u8; 8] = [0; 8];
+    writebuf[1..=cmds.len()].copy_from_slice(cmds);
+
+    (writebuf, cmds.len() + 1)
+}
+
+/// Split `buf` into chunks of at most `CHUNKLEN` bytes and hand each of them, prefixed with the
+/// data byte identifier, to `write`.
+fn write_data_chunks<E>(
+    buf: &[u8],
+    mut write: impl FnMut(&[u8]) -> Result<(), E>,
+) -> Result<(), E> {
+    // Noop if the data buffer is empty, as `chunks` yields nothing
+    let mut writebuf = [0; CHUNKLEN + 1];
+
+    for chunk in buf.chunks(CHUNKLEN) {
+        write(data_frame(&mut writebuf, chunk))?;
+    }
+
+    Ok(())
+}
+
+/// Fill `writebuf` with a data byte prefix followed by `chunk`, returning the filled part.
+fn data_frame<'a>(writebuf: &'a mut [u8; CHUNKLEN + 1], chunk: &[u8]) -> &'a [u8] {
+    writebuf[0] = 0x40; // Following bytes are data bytes
 
-            self.i2c.write(self.addr, &writebuf).map_err(Error::Comm)?;
+    // Copy over all data from buffer, leaving the data command byte intact
+    writebuf[1..=chunk.len()].copy_from_slice(chunk);
 
-            page += 1;
+    &writebuf[..=chunk.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{Recorder, Transaction};
+
+    #[test]
+    fn data_is_sent_without_addressing() {
+        let recorder = Recorder::new();
+        let mut iface = I2cInterface::new(recorder.i2c(), 0x3c);
+
+        iface.send_data(&[0xAA; 20]).unwrap();
+
+        let log = recorder.log();
+        let mut transactions = log.transactions();
+        match transactions.next() {
+            Some(Transaction::I2cWrite { addr, bytes }) => {
+                assert_eq!(addr, 0x3c);
+                assert_eq!(bytes[0], 0x40);
+                assert_eq!(&bytes[1..], &[0xAA; 20]);
+            }
+            other => panic!("unexpected transaction {:?}", other),
         }
+        assert_eq!(transactions.next(), None);
+    }
 
-        Ok(())
+    #[test]
+    fn long_data_is_split_into_chunks() {
+        let recorder = Recorder::new();
+        let mut iface = I2cInterface::new(recorder.i2c(), 0x3c);
+
+        iface.send_data(&[0x55; 132]).unwrap();
+
+        let log = recorder.log();
+        let mut transactions = log.transactions().map(|transaction| match transaction {
+            Transaction::I2cWrite { bytes, .. } => bytes,
+            other => panic!("unexpected transaction {:?}", other),
+        });
+        assert_eq!(transactions.next().map(<[u8]>::len), Some(CHUNKLEN + 1));
+        assert_eq!(
+            transactions.next(),
+            Some(&[0x40, 0x55, 0x55, 0x55, 0x55][..])
+        );
+        assert_eq!(transactions.next(), None);
+    }
+
+    #[test]
+    fn empty_data_is_noop() {
+        let recorder = Recorder::new();
+        let mut iface = I2cInterface::new(recorder.i2c(), 0x3c);
+
+        iface.send_data(&[]).unwrap();
+
+        assert!(recorder.log().is_empty());
     }
 }
@@ -5,6 +5,20 @@
 //! [connect_i2c](../builder/struct.Builder.html#method.connect_i2c) and
 //! [connect_spi](../builder/struct.Builder.html#method.connect_spi).
 //!
+//! Peripherals implementing the `embedded-hal` 1.0 traits are supported by
+//! [`I2cDeviceInterface`] and [`SpiDeviceInterface`], created with
+//! [connect_i2c_device](../builder/struct.Builder.html#method.connect_i2c_device) and
+//! [connect_spi_device](../builder/struct.Builder.html#method.connect_spi_device) respectively.
+//! With the `async` feature enabled, the same two interfaces also implement
+//! [`AsyncDisplayInterface`] for `embedded-hal-async` peripherals.
+//!
+//! Any interface from the [`display-interface`](https://crates.io/crates/display-interface)
+//! ecosystem can be used through [`DataCommandInterface`], created with
+//! [connect](../builder/struct.Builder.html#method.connect).
+//!
+//! Any of these can be wrapped in a [`ResetInterface`] to reset the display through its reset pin
+//! when initialising it, see [with_reset](../builder/struct.Builder.html#method.with_reset).
+//!
 //! The types that these interfaces define are quite lengthy, so it is recommended that you create
 //! a type alias. Here's an example for the I2C1 on an STM32F103xx:
 //!
@@ -48,7 +62,9 @@
 //! >;
 //! ```
 
+pub mod data_command;
 pub mod i2c;
+pub mod reset;
 pub mod spi;
 
 /// A method of communicating with sh1106
@@ -64,4 +80,28 @@ pub trait DisplayInterface {
     fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error>;
 }
 
-pub use self::{i2c::I2cInterface, spi::SpiInterface};
+/// An asynchronous method of communicating with sh1106
+///
+/// This is the `async` counterpart to [`DisplayInterface`], implemented for
+/// [`I2cDeviceInterface`] and [`SpiDeviceInterface`] when they wrap `embedded-hal-async`
+/// peripherals.
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait AsyncDisplayInterface {
+    /// Interface error type
+    type Error;
+
+    /// Initialize device.
+    async fn init(&mut self) -> Result<(), Self::Error>;
+    /// Send a batch of up to 8 commands to display.
+    async fn send_commands(&mut self, cmd: &[u8]) -> Result<(), Self::Error>;
+    /// Send data to display.
+    async fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error>;
+}
+
+pub use self::{
+    data_command::DataCommandInterface,
+    i2c::{I2cDeviceInterface, I2cInterface},
+    reset::ResetInterface,
+    spi::{SpiDeviceInterface, SpiInterface},
+};
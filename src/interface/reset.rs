@@ -0,0 +1,335 @@
+//! Hardware reset through a reset pin
+//!
+//! [`ResetInterface`] wraps another interface and pulses the display's reset pin whenever the
+//! display is initialised. It's created by the builder when a reset pin is given with
+//! [with_reset](../../builder/struct.Builder.html#method.with_reset).
+//!
+//! Errors setting the reset pin are reported as [`Error::Pin`], so the reset pin must have the
+//! same error type as the pins of the wrapped interface. The I2C and `display-interface` interfaces
+//! don't use any pins themselves. The builder wraps them in [`WithPinError`] to take on the error
+//! type of the reset pin instead.
+//!
+//! The reset pulse is timed with a blocking [`DelayMs`] for [`DisplayInterface`]s, and with an
+//! `embedded-hal-async` `DelayNs` for [`AsyncDisplayInterface`]s.
+//!
+//! Reset pins and delays implementing the `embedded-hal` 1.0 traits are wrapped in [`Hal1Pin`] and
+//! [`Hal1Delay`] by [with_reset_hal1](../../builder/struct.Builder.html#method.with_reset_hal1).
+
+use core::{convert::Infallible, marker::PhantomData};
+
+use hal::{blocking::delay::DelayMs, digital::v2::OutputPin};
+
+#[cfg(feature = "async")]
+use super::AsyncDisplayInterface;
+use super::DisplayInterface;
+use crate::Error;
+
+/// Time to hold the reset pin low for, in milliseconds
+pub(crate) const RESET_PULSE_MS: u8 = 10;
+
+/// Reset pin and delay, along with the timing of the reset pulse
+pub struct HardwareReset<RST, DELAY> {
+    rst: RST,
+    delay: DELAY,
+    pulse_ms: u8,
+    settle_ms: u8,
+}
+
+impl<RST, DELAY> HardwareReset<RST, DELAY> {
+    /// Create a hardware reset holding the reset pin low for 10ms
+    pub fn new(rst: RST, delay: DELAY) -> Self {
+        Self {
+            rst,
+            delay,
+            pulse_ms: RESET_PULSE_MS,
+            settle_ms: 0,
+        }
+    }
+
+    /// Set how long the reset pin is held low, and how long to wait after releasing it before
+    /// sending any commands, in milliseconds
+    pub fn with_timing(self, pulse_ms: u8, settle_ms: u8) -> Self {
+        Self {
+            pulse_ms,
+            settle_ms,
+            ..self
+        }
+    }
+
+    /// Release the reset pin and delay
+    pub fn release(self) -> (RST, DELAY) {
+        (self.rst, self.delay)
+    }
+}
+
+impl<RST, DELAY, PinE> HardwareReset<RST, DELAY>
+where
+    RST: OutputPin<Error = PinE>,
+    DELAY: DelayMs<u8>,
+{
+    /// Pulse the reset pin
+    pub fn reset(&mut self) -> Result<(), PinE> {
+        pulse(
+            &mut self.rst,
+            &mut self.delay,
+            self.pulse_ms,
+            self.settle_ms,
+        )
+    }
+}
+
+#[cfg(feature = "async")]
+impl<RST, DELAY, PinE> HardwareReset<RST, DELAY>
+where
+    RST: OutputPin<Error = PinE>,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    /// Asynchronous version of [`reset`](Self::reset), waiting with an asynchronous delay
+    pub async fn reset_async(&mut self) -> Result<(), PinE> {
+        let rst = &mut self.rst;
+
+        rst.set_high()?;
+        self.delay.delay_ms(1).await;
+        rst.set_low()?;
+        self.delay.delay_ms(self.pulse_ms.into()).await;
+        rst.set_high()?;
+
+        if self.settle_ms > 0 {
+            self.delay.delay_ms(self.settle_ms.into()).await;
+        }
+
+        Ok(())
+    }
+}
+
+/// Display interface resetting the display before initialising the wrapped interface
+pub struct ResetInterface<DI, RST, DELAY> {
+    iface: DI,
+    reset: HardwareReset<RST, DELAY>,
+}
+
+impl<DI, RST, DELAY> ResetInterface<DI, RST, DELAY> {
+    /// Wrap an interface, resetting the display with `reset` on every `init`
+    pub fn new(iface: DI, reset: HardwareReset<RST, DELAY>) -> Self {
+        Self { iface, reset }
+    }
+
+    /// Release the wrapped interface and the hardware reset
+    pub fn release(self) -> (DI, HardwareReset<RST, DELAY>) {
+        (self.iface, self.reset)
+    }
+}
+
+impl<DI, RST, DELAY, CommE, PinE> DisplayInterface for ResetInterface<DI, RST, DELAY>
+where
+    DI: DisplayInterface<Error = Error<CommE, PinE>>,
+    RST: OutputPin<Error = PinE>,
+    DELAY: DelayMs<u8>,
+{
+    type Error = Error<CommE, PinE>;
+
+    fn init(&mut self) -> Result<(), Self::Error> {
+        self.reset.reset().map_err(Error::Pin)?;
+
+        self.iface.init()
+    }
+
+    fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
+        self.iface.send_commands(cmds)
+    }
+
+    fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.iface.send_data(buf)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<DI, RST, DELAY, CommE, PinE> AsyncDisplayInterface for ResetInterface<DI, RST, DELAY>
+where
+    DI: AsyncDisplayInterface<Error = Error<CommE, PinE>>,
+    RST: OutputPin<Error = PinE>,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    type Error = Error<CommE, PinE>;
+
+    async fn init(&mut self) -> Result<(), Self::Error> {
+        self.reset.reset_async().await.map_err(Error::Pin)?;
+
+        self.iface.init().await
+    }
+
+    async fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
+        self.iface.send_commands(cmds).await
+    }
+
+    async fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.iface.send_data(buf).await
+    }
+}
+
+/// Interface without pins of its own, like I2C, reporting its errors with the pin error type
+/// `PinE` so it can be combined with a reset pin of that error type in a [`ResetInterface`]
+pub struct WithPinError<DI, PinE> {
+    iface: DI,
+    pin_error: PhantomData<PinE>,
+}
+
+impl<DI, PinE> WithPinError<DI, PinE> {
+    /// Wrap an interface without pins
+    pub fn new(iface: DI) -> Self {
+        Self {
+            iface,
+            pin_error: PhantomData,
+        }
+    }
+
+    /// Release the wrapped interface
+    pub fn release(self) -> DI {
+        self.iface
+    }
+}
+
+/// Map an error of an interface without pins to any pin error type
+fn with_pin_error<CommE, PinE>(error: Error<CommE, Infallible>) -> Error<CommE, PinE> {
+    match error {
+        Error::Comm(error) => Error::Comm(error),
+        Error::Pin(never) => match never {},
+        Error::InvalidArgument => Error::InvalidArgument,
+    }
+}
+
+impl<DI, CommE, PinE> DisplayInterface for WithPinError<DI, PinE>
+where
+    DI: DisplayInterface<Error = Error<CommE, Infallible>>,
+{
+    type Error = Error<CommE, PinE>;
+
+    fn init(&mut self) -> Result<(), Self::Error> {
+        self.iface.init().map_err(with_pin_error)
+    }
+
+    fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
+        self.iface.send_commands(cmds).map_err(with_pin_error)
+    }
+
+    fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.iface.send_data(buf).map_err(with_pin_error)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<DI, CommE, PinE> AsyncDisplayInterface for WithPinError<DI, PinE>
+where
+    DI: AsyncDisplayInterface<Error = Error<CommE, Infallible>>,
+{
+    type Error = Error<CommE, PinE>;
+
+    async fn init(&mut self) -> Result<(), Self::Error> {
+        self.iface.init().await.map_err(with_pin_error)
+    }
+
+    async fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
+        self.iface.send_commands(cmds).await.map_err(with_pin_error)
+    }
+
+    async fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.iface.send_data(buf).await.map_err(with_pin_error)
+    }
+}
+
+/// `embedded-hal` 1.0 output pin used as the reset pin of a [`HardwareReset`]
+pub struct Hal1Pin<PIN> {
+    pin: PIN,
+}
+
+impl<PIN> Hal1Pin<PIN> {
+    /// Wrap an `embedded-hal` 1.0 output pin
+    pub fn new(pin: PIN) -> Self {
+        Self { pin }
+    }
+
+    /// Release the wrapped pin
+    pub fn release(self) -> PIN {
+        self.pin
+    }
+}
+
+impl<PIN> OutputPin for Hal1Pin<PIN>
+where
+    PIN: embedded_hal_1::digital::OutputPin,
+{
+    type Error = PIN::Error;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_low()
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_high()
+    }
+}
+
+/// `embedded-hal` 1.0 delay timing the reset pulse of a [`HardwareReset`]. Blocking interfaces
+/// need an `embedded-hal` `DelayNs` and asynchronous interfaces an `embedded-hal-async` `DelayNs`.
+pub struct Hal1Delay<DELAY> {
+    delay: DELAY,
+}
+
+impl<DELAY> Hal1Delay<DELAY> {
+    /// Wrap an `embedded-hal` 1.0 delay
+    pub fn new(delay: DELAY) -> Self {
+        Self { delay }
+    }
+
+    /// Release the wrapped delay
+    pub fn release(self) -> DELAY {
+        self.delay
+    }
+}
+
+impl<DELAY> DelayMs<u8> for Hal1Delay<DELAY>
+where
+    DELAY: embedded_hal_1::delay::DelayNs,
+{
+    fn delay_ms(&mut self, ms: u8) {
+        self.delay.delay_ms(ms.into());
+    }
+}
+
+#[cfg(feature = "async")]
+impl<DELAY> embedded_hal_async::delay::DelayNs for Hal1Delay<DELAY>
+where
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    async fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns).await;
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.delay.delay_ms(ms).await;
+    }
+}
+
+/// Hold the reset pin low for `pulse_ms`, then wait `settle_ms` after releasing it
+pub(crate) fn pulse<RST, DELAY>(
+    rst: &mut RST,
+    delay: &mut DELAY,
+    pulse_ms: u8,
+    settle_ms: u8,
+) -> Result<(), RST::Error>
+where
+    RST: OutputPin,
+    DELAY: DelayMs<u8>,
+{
+    rst.set_high()?;
+    delay.delay_ms(1);
+    rst.set_low()?;
+    delay.delay_ms(pulse_ms);
+    rst.set_high()?;
+
+    if settle_ms > 0 {
+        delay.delay_ms(settle_ms);
+    }
+
+    Ok(())
+}
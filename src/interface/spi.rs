@@ -2,6 +2,8 @@
 
 use hal::{self, digital::v2::OutputPin};
 
+#[cfg(feature = "async")]
+use super::AsyncDisplayInterface;
 use super::DisplayInterface;
 use crate::Error;
 
@@ -24,6 +26,11 @@ where
     pub fn new(spi: SPI, dc: DC, cs: CS) -> Self {
         Self { spi, dc, cs }
     }
+
+    /// Release the SPI peripheral and the DC and CS pins
+    pub fn release(self) -> (SPI, DC, CS) {
+        (self.spi, self.dc, self.cs)
+    }
 }
 
 impl<SPI, DC, CS, CommE, PinE> DisplayInterface for SpiInterface<SPI, DC, CS>
@@ -42,7 +49,7 @@ where
         self.cs.set_low().map_err(Error::Pin)?;
         self.dc.set_low().map_err(Error::Pin)?;
 
-        self.spi.write(&cmds).map_err(Error::Comm)?;
+        self.spi.write(cmds).map_err(Error::Comm)?;
 
         self.dc.set_high().map_err(Error::Pin)?;
         self.cs.set_high().map_err(Error::Pin)
@@ -54,8 +61,84 @@ where
         // 1 = data, 0 = command
         self.dc.set_high().map_err(Error::Pin)?;
 
-        self.spi.write(&buf).map_err(Error::Comm)?;
+        self.spi.write(buf).map_err(Error::Comm)?;
 
         self.cs.set_high().map_err(Error::Pin)
     }
 }
+
+/// SPI display interface for `embedded-hal` 1.0 SPI devices.
+///
+/// This combines an [`SpiDevice`](embedded_hal_1::spi::SpiDevice) and a data/command pin. Chip
+/// select is managed by the `SpiDevice` implementation, so no CS pin is required here.
+pub struct SpiDeviceInterface<SPI, DC> {
+    spi: SPI,
+    dc: DC,
+}
+
+impl<SPI, DC> SpiDeviceInterface<SPI, DC> {
+    /// Create new SPI interface for communciation with sh1106
+    pub fn new(spi: SPI, dc: DC) -> Self {
+        Self { spi, dc }
+    }
+
+    /// Release the SPI device and DC pin
+    pub fn release(self) -> (SPI, DC) {
+        (self.spi, self.dc)
+    }
+}
+
+impl<SPI, DC> DisplayInterface for SpiDeviceInterface<SPI, DC>
+where
+    SPI: embedded_hal_1::spi::SpiDevice,
+    DC: embedded_hal_1::digital::OutputPin,
+{
+    type Error = Error<SPI::Error, DC::Error>;
+
+    fn init(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
+        self.dc.set_low().map_err(Error::Pin)?;
+
+        self.spi.write(cmds).map_err(Error::Comm)?;
+
+        self.dc.set_high().map_err(Error::Pin)
+    }
+
+    fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        // 1 = data, 0 = command
+        self.dc.set_high().map_err(Error::Pin)?;
+
+        self.spi.write(buf).map_err(Error::Comm)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<SPI, DC> AsyncDisplayInterface for SpiDeviceInterface<SPI, DC>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    DC: embedded_hal_1::digital::OutputPin,
+{
+    type Error = Error<SPI::Error, DC::Error>;
+
+    async fn init(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn send_commands(&mut self, cmds: &[u8]) -> Result<(), Self::Error> {
+        self.dc.set_low().map_err(Error::Pin)?;
+
+        self.spi.write(cmds).await.map_err(Error::Comm)?;
+
+        self.dc.set_high().map_err(Error::Pin)
+    }
+
+    async fn send_data(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        // 1 = data, 0 = command
+        self.dc.set_high().map_err(Error::Pin)?;
+
+        self.spi.write(buf).await.map_err(Error::Comm)
+    }
+}
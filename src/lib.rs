@@ -87,19 +87,27 @@ pub enum Error<CommE, PinE> {
     Comm(CommE),
     /// Pin setting error
     Pin(PinE),
+    /// A command argument is outside of the range accepted by the display
+    InvalidArgument,
 }
 
 extern crate embedded_hal as hal;
+#[cfg(feature = "std")]
+extern crate std;
 
 pub mod builder;
-mod command;
+pub mod command;
 pub mod displayrotation;
 pub mod displaysize;
+#[cfg(feature = "std")]
+pub mod emulator;
+pub mod initconfig;
 pub mod interface;
 pub mod mode;
 pub mod prelude;
 pub mod properties;
-#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod snapshot;
 pub mod test_helpers;
 
 pub use crate::builder::{Builder, NoOutputPin};
@@ -0,0 +1,943 @@
+//! Bufferless display mode for use with the [embedded-graphics] crate
+//!
+//! [`DirectMode`] draws straight into display RAM instead of going through a flush like
+//! [`GraphicsMode`](super::GraphicsMode). Drawing operations are sent to the display as they
+//! happen, as spans of bytes holding 8 rows of a page each. `fill_solid` and `fill_contiguous`
+//! write each page of an area once, and spans covering whole bytes, such as rectangles and images
+//! aligned to pages, are written straight through.
+//!
+//! Display RAM can't be read back through the write-only interfaces, so pixels that don't cover a
+//! whole byte are merged in a scratch copy of a single page, 132 bytes at most. It holds the page
+//! last drawn to: rows of that page that weren't drawn keep what was drawn to them before, while
+//! on other pages they are set to the color of the last [`clear`](DrawTarget::clear). Shapes
+//! crossing pages, such as lines and circles, are drawn page by page, so they only lose pixels
+//! they share a byte with if they overlap content of other pages.
+//!
+//! Where there's RAM to spare, [`with_buffer`](DirectMode::with_buffer) adds a shadow of the
+//! whole panel's display RAM in storage of your own, e.g. a `&'static mut [u8]` shared with other
+//! code. Unaligned pixels are then merged with everything drawn before, only the bytes they change
+//! are sent, and the display is redrawn after a change of rotation or flip.
+//!
+//! ```rust,no_run
+//! use embedded_graphics::{
+//!     image::{Image, ImageRaw},
+//!     pixelcolor::BinaryColor,
+//!     prelude::*,
+//!     primitives::{PrimitiveStyle, Rectangle},
+//! };
+//! use sh1106::{mode::DirectMode, Builder};
+//! # let i2c = sh1106::test_helpers::I2cStub;
+//!
+//! let mut display: DirectMode<_> = Builder::new().connect_i2c(i2c).into();
+//!
+//! display.init().unwrap();
+//!
+//! // A filled bar covering pages 1 and 2
+//! Rectangle::new(Point::new(0, 8), Size::new(128, 16))
+//!     .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+//!     .draw(&mut display)
+//!     .unwrap();
+//!
+//! // An 8x8 icon in page 4
+//! let icon = ImageRaw::<BinaryColor>::new(&[0x3C, 0x42, 0xA5, 0x81, 0xA5, 0x99, 0x42, 0x3C], 8);
+//!
+//! Image::new(&icon, Point::new(60, 32))
+//!     .draw(&mut display)
+//!     .unwrap();
+//! ```
+//!
+//! In the 90 and 270 degree rotations, `fill_contiguous` is drawn pixel by pixel as the order of
+//! its colors runs across pages.
+//!
+//! [embedded-graphics]: https://crates.io/crates/embedded-graphics
+
+use core::{marker::PhantomData, ops::Range};
+
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Size},
+    pixelcolor::BinaryColor,
+    primitives::{PointsIter, Rectangle},
+    Pixel,
+};
+use hal::{blocking::delay::DelayMs, digital::v2::OutputPin};
+
+use crate::{
+    command::Command,
+    displayrotation::DisplayRotation,
+    displaysize::{DynamicSize, PanelSize},
+    interface::DisplayInterface,
+    mode::displaymode::DisplayModeTrait,
+    properties::DisplayProperties,
+    Error,
+};
+
+/// Number of columns in display RAM, the most a page of the panel can hold
+const RAM_COLUMNS: usize = 132;
+
+/// Direct drawing mode handler
+///
+/// `SIZE` checks the panel size like for [`GraphicsMode`](super::GraphicsMode). `BUF` is the type
+/// of the optional shadow of display RAM given to [`with_buffer`](Self::with_buffer); modes
+/// created with `into` have no shadow.
+pub struct DirectMode<DI, SIZE = DynamicSize, BUF = &'static mut [u8]>
+where
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
+{
+    properties: DisplayProperties<DI>,
+    /// Contents of the panel's display RAM, one byte for each column of each page
+    shadow: Option<BUF>,
+    /// Contents of the panel columns of `scratch_page` when there's no shadow
+    scratch: [u8; RAM_COLUMNS],
+    scratch_page: Option<u8>,
+    /// Value display RAM was last cleared to, assumed for rows of other pages
+    background: u8,
+    size: PhantomData<SIZE>,
+}
+
+impl<DI, SIZE> DisplayModeTrait<DI> for DirectMode<DI, SIZE>
+where
+    SIZE: PanelSize,
+{
+    /// Create new DirectMode instance
+    fn new(mut properties: DisplayProperties<DI>) -> Self {
+        properties.apply_panel_size::<SIZE>();
+
+        DirectMode {
+            properties,
+            shadow: None,
+            scratch: [0; RAM_COLUMNS],
+            scratch_page: None,
+            background: 0x00,
+            size: PhantomData,
+        }
+    }
+
+    /// Release all resources used by DirectMode
+    fn release(self) -> DisplayProperties<DI> {
+        self.properties
+    }
+}
+
+impl<DI, SIZE, BUF> DirectMode<DI, SIZE, BUF>
+where
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Create a direct mode keeping a shadow of the whole panel's display RAM in caller-provided
+    /// storage, see [`GraphicsMode::with_buffer`](super::GraphicsMode::with_buffer)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shadow` is too small for the display size, or if `SIZE` is a fixed panel size
+    /// that doesn't match the size set with [`Builder::with_size`](crate::Builder::with_size).
+    pub fn with_buffer(mut properties: DisplayProperties<DI>, mut shadow: BUF) -> Self {
+        properties.apply_panel_size::<SIZE>();
+
+        let (width, height) = properties.get_size().dimensions();
+        assert!(
+            shadow.as_ref().len() >= width as usize * (height / 8) as usize,
+            "buffer is too small for the display size"
+        );
+        shadow.as_mut().fill(0);
+
+        DirectMode {
+            properties,
+            shadow: Some(shadow),
+            scratch: [0; RAM_COLUMNS],
+            scratch_page: None,
+            background: 0x00,
+            size: PhantomData,
+        }
+    }
+
+    /// Release the display properties along with the shadow storage, if the mode was created
+    /// with one
+    pub fn release_buffer(self) -> (DisplayProperties<DI>, Option<BUF>) {
+        (self.properties, self.shadow)
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn get_dimensions(&self) -> (u8, u8) {
+        self.properties.get_dimensions()
+    }
+
+    /// Whether display coordinates are transposed to get panel coordinates in the current
+    /// rotation
+    fn transposed(&self) -> bool {
+        matches!(
+            self.properties.get_rotation(),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270
+        )
+    }
+
+    /// Known contents of a page, one byte for each panel column. Without a shadow, the scratch
+    /// page is loaded with the background if it held another page.
+    fn page_mut(&mut self, page: u8) -> &mut [u8] {
+        let width = self.properties.get_size().dimensions().0 as usize;
+
+        match &mut self.shadow {
+            Some(shadow) => &mut shadow.as_mut()[page as usize * width..][..width],
+            None => {
+                if self.scratch_page != Some(page) {
+                    self.scratch.fill(self.background);
+                    self.scratch_page = Some(page);
+                }
+
+                &mut self.scratch[..width]
+            }
+        }
+    }
+}
+
+impl<DI, SIZE, BUF> DirectMode<DI, SIZE, BUF>
+where
+    DI: DisplayInterface,
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Initialise the display in column mode and clear the display RAM of the panel
+    pub fn init(&mut self) -> Result<(), DI::Error> {
+        self.properties.init_column_mode()?;
+
+        self.fill_pages(0x00)
+    }
+
+    /// Initialise the display following the power-on sequence from the datasheet and clear the
+    /// display RAM of the panel, see
+    /// [`DisplayProperties::init_column_mode_with_delay`](crate::properties::DisplayProperties::init_column_mode_with_delay)
+    pub fn init_with_delay<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        self.properties.init_column_mode_with_delay(delay)?;
+
+        self.fill_pages(0x00)
+    }
+
+    /// Turn the display and charge pump off. Display RAM keeps its contents while asleep.
+    pub fn sleep(&mut self) -> Result<(), DI::Error> {
+        self.properties.sleep()
+    }
+
+    /// Turn the charge pump and display back on, see
+    /// [`DisplayProperties::wake`](crate::properties::DisplayProperties::wake)
+    pub fn wake(&mut self) -> Result<(), DI::Error> {
+        self.properties.wake()
+    }
+
+    /// Turn the charge pump and display back on, waiting for the charge pump to settle
+    pub fn wake_with_delay<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        self.properties.wake_with_delay(delay)
+    }
+
+    /// Set the display rotation
+    ///
+    /// The panel may show a different range of display RAM columns in the new rotation. With a
+    /// shadow, its contents are sent to the display again; without one, what was drawn should be
+    /// drawn again.
+    pub fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), DI::Error> {
+        self.properties.set_rotation(rot)?;
+
+        self.draw_pages()
+    }
+
+    /// Mirror the display horizontally and/or vertically. Like for
+    /// [`set_rotation`](Self::set_rotation), the shadow is sent to the display again if there is
+    /// one.
+    pub fn set_flip(&mut self, flip_x: bool, flip_y: bool) -> Result<(), DI::Error> {
+        self.properties.set_flip(flip_x, flip_y)?;
+
+        self.draw_pages()
+    }
+
+    /// Set the display contrast
+    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), DI::Error> {
+        self.properties.set_contrast(contrast)
+    }
+
+    /// Set the draw area to the given columns of the panel, covering every page that `rows` touch
+    fn set_area(&mut self, columns: Range<u8>, rows: Range<u8>) -> Result<(), DI::Error> {
+        let column_offset = self.properties.get_column_offset();
+        let first_page = rows.start / 8;
+        let last_page = (rows.end - 1) / 8;
+
+        self.properties.set_draw_area(
+            (column_offset + columns.start, first_page * 8),
+            (column_offset + columns.end, (last_page + 1) * 8),
+        )
+    }
+
+    /// Set every byte of the panel's display RAM to `value`
+    fn fill_pages(&mut self, value: u8) -> Result<(), DI::Error> {
+        let (width, height) = self.properties.get_size().dimensions();
+
+        self.background = value;
+        self.scratch_page = None;
+
+        if let Some(shadow) = &mut self.shadow {
+            shadow.as_mut().fill(value);
+        }
+
+        self.set_area(0..width, 0..height)?;
+
+        for _ in 0..height / 8 {
+            self.properties
+                .draw(&[value; RAM_COLUMNS][..width as usize])?;
+        }
+
+        Ok(())
+    }
+
+    /// Send the whole shadow to the panel's display RAM. Without a shadow, the scratch page is
+    /// forgotten as its columns may now be at other display RAM columns.
+    fn draw_pages(&mut self) -> Result<(), DI::Error> {
+        let (width, height) = self.properties.get_size().dimensions();
+
+        self.scratch_page = None;
+
+        if self.shadow.is_none() {
+            return Ok(());
+        }
+
+        self.set_area(0..width, 0..height)?;
+
+        for page in 0..height / 8 {
+            self.draw_known(page, 0..width)?;
+        }
+
+        Ok(())
+    }
+
+    /// Send the given panel columns of a page from its known contents, continuing the current draw
+    /// area
+    fn draw_known(&mut self, page: u8, columns: Range<u8>) -> Result<(), DI::Error> {
+        let width = self.properties.get_size().dimensions().0 as usize;
+        let known = match &self.shadow {
+            Some(shadow) => &shadow.as_ref()[page as usize * width..][..width],
+            None => &self.scratch[..width],
+        };
+
+        self.properties
+            .draw(&known[columns.start as usize..columns.end as usize])
+    }
+
+    /// Merge `bits` into the rows of `mask` in a page, starting at panel column `start`, and send
+    /// the bytes in between the first and last one to send
+    ///
+    /// Bytes covering all rows are written straight through. Other bytes are merged with the
+    /// known contents of the page; with a shadow, only those that change are sent.
+    fn merge_page(&mut self, page: u8, start: u8, mask: u8, bits: &[u8]) -> Result<(), DI::Error> {
+        let columns = start..start + bits.len() as u8;
+
+        if mask == 0xFF {
+            if self.shadow.is_some() || self.scratch_page == Some(page) {
+                self.page_mut(page)[columns.start as usize..columns.end as usize]
+                    .copy_from_slice(bits);
+            }
+
+            self.set_area(columns, page * 8..page * 8 + 1)?;
+
+            return self.properties.draw(bits);
+        }
+
+        // Display RAM may differ from a scratch page, so all drawn bytes are sent without a shadow
+        let send_unchanged = self.shadow.is_none();
+        let known = &mut self.page_mut(page)[columns.start as usize..columns.end as usize];
+        let mut changed: Option<Range<u8>> = None;
+
+        for (column, (byte, &bits)) in columns.zip(known.iter_mut().zip(bits)) {
+            let merged = (*byte & !mask) | (bits & mask);
+
+            if core::mem::replace(byte, merged) != merged || send_unchanged {
+                changed = match changed {
+                    Some(changed) => Some(changed.start..column + 1),
+                    None => Some(column..column + 1),
+                };
+            }
+        }
+
+        match changed {
+            Some(columns) => {
+                self.set_area(columns.clone(), page * 8..page * 8 + 1)?;
+                self.draw_known(page, columns)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Merge the pixels of a page drawn by `draw_iter`, given as the bits and mask of drawn rows
+    /// for each panel column, sending one write for each contiguous run of columns to send
+    fn merge_masked(
+        &mut self,
+        page: u8,
+        bits: &[u8; RAM_COLUMNS],
+        masks: &[u8; RAM_COLUMNS],
+    ) -> Result<(), DI::Error> {
+        let (width, _) = self.properties.get_size().dimensions();
+        let send_unchanged = self.shadow.is_none();
+        let mut run = None;
+
+        for column in 0..=width {
+            let send = column < width && {
+                let mask = masks[column as usize];
+                let byte = &mut self.page_mut(page)[column as usize];
+                let merged = (*byte & !mask) | (bits[column as usize] & mask);
+
+                mask != 0 && (core::mem::replace(byte, merged) != merged || send_unchanged)
+            };
+
+            match (run, send) {
+                (None, true) => run = Some(column),
+                (Some(start), false) => {
+                    self.set_area(start..column, page * 8..page * 8 + 1)?;
+                    self.draw_known(page, start..column)?;
+                    run = None;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<DI, SIZE, BUF, CommE, PinE> DirectMode<DI, SIZE, BUF>
+where
+    DI: DisplayInterface<Error = Error<CommE, PinE>>,
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Send a single command to the display, see
+    /// [`DisplayProperties::send_command`](crate::properties::DisplayProperties::send_command).
+    pub fn send_command(&mut self, command: Command) -> Result<(), DI::Error> {
+        self.properties.send_command(command)
+    }
+
+    /// Reset the display by pulsing its reset pin, see
+    /// [`DisplayProperties::reset`](crate::properties::DisplayProperties::reset)
+    pub fn reset<RST, DELAY>(
+        &mut self,
+        rst: &mut RST,
+        delay: &mut DELAY,
+    ) -> Result<(), Error<CommE, RST::Error>>
+    where
+        RST: OutputPin,
+        DELAY: DelayMs<u8>,
+    {
+        self.properties.reset(rst, delay)
+    }
+}
+
+impl<DI, SIZE, BUF> DrawTarget for DirectMode<DI, SIZE, BUF>
+where
+    DI: DisplayInterface,
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
+{
+    type Color = BinaryColor;
+    type Error = DI::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+        let transposed = self.transposed();
+
+        // Pixels of the current page, merged once drawing moves on to another page
+        let mut page = None;
+        let mut bits = [0; RAM_COLUMNS];
+        let mut masks = [0; RAM_COLUMNS];
+
+        for Pixel(pos, color) in pixels.into_iter().filter(|Pixel(pos, _)| bb.contains(*pos)) {
+            let (column, row) = if transposed {
+                (pos.y as usize, pos.x as u8)
+            } else {
+                (pos.x as usize, pos.y as u8)
+            };
+
+            if page != Some(row / 8) {
+                if let Some(page) = page {
+                    self.merge_masked(page, &bits, &masks)?;
+                }
+
+                page = Some(row / 8);
+                masks = [0; RAM_COLUMNS];
+            }
+
+            let bit = 1 << (row % 8);
+            if color.is_on() {
+                bits[column] |= bit;
+            } else {
+                bits[column] &= !bit;
+            }
+            masks[column] |= bit;
+        }
+
+        match page {
+            Some(page) => self.merge_masked(page, &bits, &masks),
+            None => Ok(()),
+        }
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        if self.transposed() {
+            return self.draw_iter(
+                area.points()
+                    .zip(colors)
+                    .map(|(pos, color)| Pixel(pos, color)),
+            );
+        }
+
+        let drawable = area.intersection(&self.bounding_box());
+
+        if drawable.is_zero_sized() {
+            return Ok(());
+        }
+
+        let (x, y) = (drawable.top_left.x as u8, drawable.top_left.y as u8);
+        let (width, height) = (drawable.size.width as u8, drawable.size.height as u8);
+
+        // Colors are given row by row, so each page is complete once a row of the next one starts
+        let mut page = y / 8;
+        let mut bits = [0; RAM_COLUMNS];
+
+        for (pos, color) in area.points().zip(colors) {
+            if !drawable.contains(pos) {
+                continue;
+            }
+
+            let row = pos.y as u8;
+
+            if row / 8 != page {
+                let mask = page_mask(page, y, height);
+                self.merge_page(page, x, mask, &bits[..width as usize])?;
+
+                page = row / 8;
+                bits = [0; RAM_COLUMNS];
+            }
+
+            if color.is_on() {
+                bits[(pos.x as u8 - x) as usize] |= 1 << (row % 8);
+            }
+        }
+
+        let mask = page_mask(page, y, height);
+        self.merge_page(page, x, mask, &bits[..width as usize])
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+
+        let (mut x, mut y) = (area.top_left.x as u8, area.top_left.y as u8);
+        let (mut width, mut height) = (area.size.width as u8, area.size.height as u8);
+
+        if self.transposed() {
+            core::mem::swap(&mut x, &mut y);
+            core::mem::swap(&mut width, &mut height);
+        }
+
+        let value = if color.is_on() { 0xFF } else { 0x00 };
+
+        for page in y / 8..=(y + height - 1) / 8 {
+            let mask = page_mask(page, y, height);
+            self.merge_page(page, x, mask, &[value; RAM_COLUMNS][..width as usize])?;
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_pages(if color.is_on() { 0xFF } else { 0x00 })
+    }
+}
+
+impl<DI, SIZE, BUF> OriginDimensions for DirectMode<DI, SIZE, BUF>
+where
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
+{
+    fn size(&self) -> Size {
+        let (w, h) = self.get_dimensions();
+
+        Size::new(w.into(), h.into())
+    }
+}
+
+/// Rows of `page` covered by `height` rows starting at row `y`, as a byte mask
+fn page_mask(page: u8, y: u8, height: u8) -> u8 {
+    let first = y.max(page * 8) - page * 8;
+    let last = (y + height).min(page * 8 + 8) - page * 8;
+
+    ((1u16 << last) - (1u16 << first)) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::Builder,
+        displaysize::{DisplaySize, Size128x32},
+        test_helpers::{I2cStub, Recorder, Transaction},
+    };
+    use embedded_graphics_core::geometry::Point;
+
+    #[test]
+    #[should_panic(expected = "panel size of the mode doesn't match")]
+    fn fixed_size_rejects_other_builder_size() {
+        let _: DirectMode<_, Size128x32> = Builder::new()
+            .with_size(DisplaySize::Display128x64NoOffset)
+            .connect_i2c(I2cStub)
+            .into();
+    }
+
+    #[test]
+    #[should_panic(expected = "panel size of the mode doesn't match")]
+    fn caller_shadow_rejects_other_builder_size() {
+        let mut shadow = [0u8; 132 * 64 / 8];
+
+        let _: DirectMode<_, Size128x32, _> = DirectMode::with_buffer(
+            Builder::new()
+                .with_size(DisplaySize::Display132x64)
+                .connect_i2c(I2cStub)
+                .release(),
+            &mut shadow[..],
+        );
+    }
+
+    #[test]
+    fn fill_solid_writes_each_page_once() {
+        let recorder = Recorder::new();
+        let mut display: DirectMode<_> = Builder::new().connect_i2c(recorder.i2c()).into();
+
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(10, 4), Size::new(6, 16)),
+                BinaryColor::On,
+            )
+            .unwrap();
+
+        let log = recorder.log();
+        let mut transactions = log.transactions().map(|t| match t {
+            Transaction::I2cWrite { bytes, .. } => bytes,
+            _ => panic!("unexpected transaction {:?}", t),
+        });
+
+        // Draw area starting at page 0, column 10 + 2, then one data write per page
+        assert!(transactions.by_ref().take(3).eq([
+            &[0x00, 0xB0][..],
+            &[0x00, 0x0C],
+            &[0x00, 0x10]
+        ]));
+        assert!(transactions.filter(|bytes| bytes[0] == 0x40).eq([
+            &[0x40, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0][..],
+            &[0x40, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+            &[0x40, 0x0F, 0x0F, 0x0F, 0x0F, 0x0F, 0x0F],
+        ]));
+    }
+
+    #[test]
+    fn draw_iter_sends_touched_runs_per_page() {
+        let recorder = Recorder::new();
+        let mut display: DirectMode<_> = Builder::new().connect_i2c(recorder.i2c()).into();
+
+        // Two runs on page 0, then one on page 1
+        let pixels = [(0, 0), (1, 1), (0, 2), (50, 7), (3, 8)]
+            .iter()
+            .map(|&(x, y)| Pixel(Point::new(x, y), BinaryColor::On));
+        display.draw_iter(pixels).unwrap();
+
+        let log = recorder.log();
+        let data = log.transactions().filter_map(|t| match t {
+            Transaction::I2cWrite { bytes, .. } if bytes[0] == 0x40 => Some(bytes),
+            _ => None,
+        });
+
+        assert!(data.eq([&[0x40, 0x05, 0x02][..], &[0x40, 0x80], &[0x40, 0x01]]));
+    }
+
+    #[test]
+    fn unaligned_draws_keep_other_rows() {
+        let recorder = Recorder::new();
+        let mut display: DirectMode<_> = Builder::new().connect_i2c(recorder.i2c()).into();
+
+        let pixel = |x, y, color| Pixel(Point::new(x, y), color);
+        display.draw_iter([pixel(3, 1, BinaryColor::On)]).unwrap();
+        display.draw_iter([pixel(3, 6, BinaryColor::On)]).unwrap();
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(2, 4), Size::new(3, 1)),
+                BinaryColor::On,
+            )
+            .unwrap();
+        display.draw_iter([pixel(3, 1, BinaryColor::Off)]).unwrap();
+        // Without a shadow, display RAM may not match the scratch page, so drawn bytes are always
+        // sent
+        display.draw_iter([pixel(3, 6, BinaryColor::On)]).unwrap();
+
+        let log = recorder.log();
+        let data = log.transactions().filter_map(|t| match t {
+            Transaction::I2cWrite { bytes, .. } if bytes[0] == 0x40 => Some(bytes),
+            _ => None,
+        });
+
+        assert!(data.eq([
+            &[0x40, 0x02][..],
+            &[0x40, 0x42],
+            &[0x40, 0x10, 0x52, 0x10],
+            &[0x40, 0x50],
+            &[0x40, 0x50],
+        ]));
+    }
+
+    #[test]
+    fn other_pages_are_assumed_cleared() {
+        let recorder = Recorder::new();
+        let mut display: DirectMode<_> = Builder::new().connect_i2c(recorder.i2c()).into();
+
+        let pixel = |x, y| Pixel(Point::new(x, y), BinaryColor::On);
+        display.clear(BinaryColor::On).unwrap();
+        display.draw_iter([pixel(3, 1)]).unwrap();
+        display.clear(BinaryColor::Off).unwrap();
+        display.draw_iter([pixel(3, 1)]).unwrap();
+        display.draw_iter([pixel(3, 9)]).unwrap();
+        // Page 0 is no longer in the scratch page, so the first pixel is forgotten
+        display.draw_iter([pixel(3, 2)]).unwrap();
+        // Whole bytes are written without replacing the scratch page
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(0, 8), Size::new(2, 8)),
+                BinaryColor::On,
+            )
+            .unwrap();
+        display.draw_iter([pixel(3, 3)]).unwrap();
+
+        let log = recorder.log();
+        let data = log.transactions().filter_map(|t| match t {
+            Transaction::I2cWrite { bytes, .. } if bytes[0] == 0x40 && bytes.len() < 10 => {
+                Some(bytes)
+            }
+            _ => None,
+        });
+
+        assert!(data.eq([
+            &[0x40, 0xFF][..],
+            &[0x40, 0x02],
+            &[0x40, 0x02],
+            &[0x40, 0x04],
+            &[0x40, 0xFF, 0xFF],
+            &[0x40, 0x0C],
+        ]));
+    }
+
+    #[test]
+    fn shadow_only_sends_changed_bytes() {
+        let recorder = Recorder::new();
+        let mut shadow = [0; 128 * 64 / 8];
+        let mut display: DirectMode<_, DynamicSize, _> = DirectMode::with_buffer(
+            Builder::new().connect_i2c(recorder.i2c()).release(),
+            &mut shadow[..],
+        );
+
+        let pixel = |x, y, color| Pixel(Point::new(x, y), color);
+        display.draw_iter([pixel(3, 1, BinaryColor::On)]).unwrap();
+        display.draw_iter([pixel(3, 9, BinaryColor::On)]).unwrap();
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(2, 6), Size::new(3, 1)),
+                BinaryColor::On,
+            )
+            .unwrap();
+        display.draw_iter([pixel(3, 6, BinaryColor::On)]).unwrap();
+
+        let log = recorder.log();
+        let data = log.transactions().filter_map(|t| match t {
+            Transaction::I2cWrite { bytes, .. } if bytes[0] == 0x40 => Some(bytes),
+            _ => None,
+        });
+
+        // Page 0 keeps the first pixel after drawing to page 1, and the last pixel changes nothing
+        assert!(data.eq([&[0x40, 0x02][..], &[0x40, 0x02], &[0x40, 0x40, 0x42, 0x40],]));
+    }
+
+    #[test]
+    fn shadow_can_be_sized_to_panel() {
+        let mut shadow = [0xFF; 128 * 32 / 8];
+        let mut display: DirectMode<_, DynamicSize, _> = DirectMode::with_buffer(
+            Builder::new()
+                .with_size(DisplaySize::Display128x32)
+                .connect_i2c(I2cStub)
+                .release(),
+            &mut shadow[..],
+        );
+
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(0, 30), Size::new(128, 10)),
+                BinaryColor::On,
+            )
+            .unwrap();
+
+        let (_, shadow) = display.release_buffer();
+        let shadow = shadow.unwrap();
+        assert_eq!(shadow[3 * 128..], [0xC0; 128]);
+        assert!(shadow[..3 * 128].iter().all(|&byte| byte == 0));
+    }
+
+    #[cfg(feature = "std")]
+    mod emulated {
+        use super::*;
+        use crate::{displaysize::DisplaySize, emulator::Emulator, mode::GraphicsMode};
+        use embedded_graphics::{
+            image::{Image, ImageRaw},
+            prelude::*,
+            primitives::{Circle, Line, PrimitiveStyle},
+        };
+
+        const ICON: ImageRaw<'static, BinaryColor> =
+            ImageRaw::new(&[0x3C, 0x42, 0xA5, 0x81, 0xA5, 0x99, 0x42, 0x3C], 8);
+
+        /// Drawing done the same way on both modes
+        trait Scene {
+            fn draw<D: DrawTarget<Color = BinaryColor>>(
+                &self,
+                target: &mut D,
+            ) -> Result<(), D::Error>;
+        }
+
+        /// Draw `scene` with a direct mode, with or without a shadow, and with a graphics mode in
+        /// each rotation, then rotate both by 180 degrees and compare what the panels show. Without
+        /// a shadow, the scene is drawn again after rotating.
+        fn assert_matches_graphics_mode(scene: impl Scene, shadow: bool) {
+            let rotations = [
+                DisplayRotation::Rotate0,
+                DisplayRotation::Rotate90,
+                DisplayRotation::Rotate180,
+                DisplayRotation::Rotate270,
+            ];
+            let sizes = [
+                DisplaySize::Display128x64NoOffset,
+                DisplaySize::Display128x32,
+            ];
+
+            for (&size, &rotation) in sizes
+                .iter()
+                .flat_map(|size| rotations.iter().map(move |rotation| (size, rotation)))
+            {
+                let builder = Builder::new().with_size(size).with_rotation(rotation);
+
+                let mut direct_emulator = Emulator::new(size);
+                let mut storage = [0; 132 * 64 / 8];
+                let properties = builder.connect_interface(&mut direct_emulator).release();
+                let mut direct: DirectMode<_, DynamicSize, _> = if shadow {
+                    DirectMode::with_buffer(properties, &mut storage[..])
+                } else {
+                    DirectMode::new(properties)
+                };
+                direct.init().unwrap();
+                scene.draw(&mut direct).unwrap();
+
+                let mut buffered_emulator = Emulator::new(size);
+                let mut buffered: GraphicsMode<_> =
+                    builder.connect_interface(&mut buffered_emulator).into();
+                buffered.init().unwrap();
+                scene.draw(&mut buffered).unwrap();
+                buffered.flush().unwrap();
+
+                direct.set_rotation(DisplayRotation::Rotate180).unwrap();
+                buffered.set_rotation(DisplayRotation::Rotate180).unwrap();
+
+                // The shadow is sent again, otherwise the scene has to be drawn again
+                if !shadow {
+                    direct.clear(BinaryColor::Off).unwrap();
+                    scene.draw(&mut direct).unwrap();
+                    buffered.clear();
+                    scene.draw(&mut buffered).unwrap();
+                }
+                buffered.flush().unwrap();
+
+                assert_eq!(
+                    direct_emulator
+                        .snapshot()
+                        .compare_image(&buffered_emulator.snapshot()),
+                    Ok(()),
+                    "{:?} {:?}",
+                    size.dimensions(),
+                    rotation
+                );
+            }
+        }
+
+        #[test]
+        fn drawing_with_shadow_matches_graphics_mode() {
+            struct Overlapping;
+
+            impl Scene for Overlapping {
+                fn draw<D: DrawTarget<Color = BinaryColor>>(
+                    &self,
+                    target: &mut D,
+                ) -> Result<(), D::Error> {
+                    Rectangle::new(Point::new(-5, 8), Size::new(40, 16))
+                        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+                        .draw(target)?;
+                    Image::new(&ICON, Point::new(60, 32)).draw(target)?;
+                    Image::new(&ICON, Point::new(124, 56)).draw(target)?;
+
+                    // Shapes that aren't aligned to pages, overlapping what was drawn before
+                    Circle::new(Point::new(20, 3), 30)
+                        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+                        .draw(target)?;
+                    Line::new(Point::new(0, 63), Point::new(127, 0))
+                        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+                        .draw(target)?;
+                    Rectangle::new(Point::new(58, 35), Size::new(6, 3))
+                        .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+                        .draw(target)?;
+                    Image::new(&ICON, Point::new(90, 13)).draw(target)
+                }
+            }
+
+            assert_matches_graphics_mode(Overlapping, true);
+        }
+
+        #[test]
+        fn drawing_without_shadow_matches_graphics_mode() {
+            struct Separate;
+
+            impl Scene for Separate {
+                fn draw<D: DrawTarget<Color = BinaryColor>>(
+                    &self,
+                    target: &mut D,
+                ) -> Result<(), D::Error> {
+                    Rectangle::new(Point::new(-5, 8), Size::new(40, 16))
+                        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+                        .draw(target)?;
+                    Image::new(&ICON, Point::new(8, 40)).draw(target)?;
+                    Image::new(&ICON, Point::new(124, 56)).draw(target)?;
+
+                    // Shapes crossing pages that only share bytes with their own pixels
+                    Line::new(Point::new(0, 63), Point::new(127, 0))
+                        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+                        .draw(target)?;
+                    Circle::new(Point::new(90, 40), 12)
+                        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+                        .draw(target)
+                }
+            }
+
+            assert_matches_graphics_mode(Separate, false);
+        }
+    }
+}
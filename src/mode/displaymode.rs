@@ -1,6 +1,6 @@
 //! Abstraction of different operating modes for the sh1106
 
-use crate::{interface::DisplayInterface, properties::DisplayProperties};
+use crate::properties::DisplayProperties;
 
 /// Display mode abstraction
 pub struct DisplayMode<MODE>(pub MODE);
@@ -18,7 +18,6 @@ impl<MODE> DisplayMode<MODE> {
     /// Setup display to run in requested mode
     pub fn new<DI>(properties: DisplayProperties<DI>) -> Self
     where
-        DI: DisplayInterface,
         MODE: DisplayModeTrait<DI>,
     {
         DisplayMode(MODE::new(properties))
@@ -28,10 +27,18 @@ impl<MODE> DisplayMode<MODE> {
     // TODO: Figure out how to stay as generic DisplayMode but act as particular mode
     pub fn into<DI, NMODE: DisplayModeTrait<DI>>(self) -> NMODE
     where
-        DI: DisplayInterface,
         MODE: DisplayModeTrait<DI>,
     {
         let properties = self.0.release();
         NMODE::new(properties)
     }
+
+    /// Release the display properties, e.g. to recover the interface with
+    /// [`DisplayProperties::release`]
+    pub fn release<DI>(self) -> DisplayProperties<DI>
+    where
+        MODE: DisplayModeTrait<DI>,
+    {
+        self.0.release()
+    }
 }
@@ -42,34 +42,73 @@
 //! display.flush().unwrap();
 //! ```
 
+use core::{marker::PhantomData, ops::Range};
+
 use hal::{blocking::delay::DelayMs, digital::v2::OutputPin};
 
+#[cfg(feature = "async")]
+use crate::interface::AsyncDisplayInterface;
+#[cfg(feature = "std")]
+use crate::snapshot::Image;
 use crate::{
-    displayrotation::DisplayRotation, interface::DisplayInterface,
-    mode::displaymode::DisplayModeTrait, properties::DisplayProperties, Error,
+    command::Command,
+    displayrotation::DisplayRotation,
+    displaysize::{DynamicSize, PanelSize},
+    interface::DisplayInterface,
+    mode::displaymode::DisplayModeTrait,
+    properties::DisplayProperties,
+    Error,
 };
 
-const BUFFER_SIZE: usize = 132 * 64 / 8;
+const PAGES: usize = 64 / 8;
+
+/// Number of columns in display RAM, the most a page of the panel can hold
+#[cfg(feature = "graphics")]
+const RAM_COLUMNS: usize = 132;
+
+/// Column span of a page that is clean, i.e. hasn't changed since the last flush
+const CLEAN: (u8, u8) = (u8::MAX, 0);
 
 /// Graphics mode handler
-pub struct GraphicsMode<DI>
+///
+/// The framebuffer is sized according to `SIZE`, which defaults to a buffer large enough for any
+/// [`DisplaySize`](crate::displaysize::DisplaySize). See [`PanelSize`] for using a smaller buffer.
+///
+/// The framebuffer is stored inline by default. Use [`with_buffer`](GraphicsMode::with_buffer)
+/// to provide the storage yourself, e.g. a `&'static mut [u8]`.
+pub struct GraphicsMode<DI, SIZE = DynamicSize, BUF = <SIZE as PanelSize>::Buffer>
 where
-    DI: DisplayInterface,
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
 {
     properties: DisplayProperties<DI>,
-    buffer: [u8; BUFFER_SIZE],
+    buffer: BUF,
+    /// Half-open range of buffer columns changed since the last flush, for each page
+    dirty: [(u8, u8); PAGES],
+    /// Display RAM row shown on the first line of the panel
+    start_line: u8,
+    size: PhantomData<SIZE>,
 }
 
-impl<DI> DisplayModeTrait<DI> for GraphicsMode<DI>
+impl<DI, SIZE> DisplayModeTrait<DI> for GraphicsMode<DI, SIZE>
 where
-    DI: DisplayInterface,
+    SIZE: PanelSize,
 {
     /// Create new GraphicsMode instance
-    fn new(properties: DisplayProperties<DI>) -> Self {
-        GraphicsMode {
+    fn new(mut properties: DisplayProperties<DI>) -> Self {
+        properties.apply_panel_size::<SIZE>();
+
+        let mut mode = GraphicsMode {
             properties,
-            buffer: [0; BUFFER_SIZE],
-        }
+            buffer: SIZE::buffer(),
+            dirty: [CLEAN; PAGES],
+            start_line: 0,
+            size: PhantomData,
+        };
+
+        mode.mark_all_dirty();
+
+        mode
     }
 
     /// Release all resources used by GraphicsMode
@@ -78,135 +117,754 @@ where
     }
 }
 
-impl<DI> GraphicsMode<DI>
+impl<DI, SIZE, BUF> GraphicsMode<DI, SIZE, BUF>
 where
-    DI: DisplayInterface,
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
 {
+    /// Create a graphics mode drawing into caller-provided storage instead of a buffer of its
+    /// own. The buffer is cleared and needs to hold `width * height / 8` bytes for the display
+    /// size selected by `SIZE`, any bytes past that are left unused.
+    ///
+    /// ```rust
+    /// use sh1106::{displaysize::DynamicSize, prelude::*, Builder};
+    /// # let i2c = sh1106::test_helpers::I2cStub;
+    ///
+    /// let mut buffer = [0u8; 128 * 32 / 8];
+    /// let properties = Builder::new()
+    ///     .with_size(DisplaySize::Display128x32)
+    ///     .connect_i2c(i2c)
+    ///     .release();
+    ///
+    /// let display: GraphicsMode<_, DynamicSize, _> =
+    ///     GraphicsMode::with_buffer(properties, &mut buffer[..]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is too small for the display size, or if `SIZE` is a fixed panel size
+    /// that doesn't match the size set with [`Builder::with_size`](crate::Builder::with_size).
+    pub fn with_buffer(mut properties: DisplayProperties<DI>, mut buffer: BUF) -> Self {
+        properties.apply_panel_size::<SIZE>();
+
+        let (width, height) = properties.get_size().dimensions();
+        let len = width as usize * (height / 8) as usize;
+        assert!(
+            buffer.as_ref().len() >= len,
+            "buffer is too small for the display size"
+        );
+        buffer.as_mut().fill(0);
+
+        let mut mode = GraphicsMode {
+            properties,
+            buffer,
+            dirty: [CLEAN; PAGES],
+            start_line: 0,
+            size: PhantomData,
+        };
+
+        mode.mark_all_dirty();
+
+        mode
+    }
+
+    /// Release the display properties along with the framebuffer storage, e.g. to hand the
+    /// storage over to another display
+    pub fn release_buffer(self) -> (DisplayProperties<DI>, BUF) {
+        (self.properties, self.buffer)
+    }
+
     /// Clear the display buffer. You need to call `display.flush()` for any effect on the screen
     pub fn clear(&mut self) {
-        self.buffer = [0; BUFFER_SIZE];
+        self.buffer.as_mut().fill(0);
+        self.mark_all_dirty();
     }
 
-    /// Reset display
-    pub fn reset<RST, DELAY, PinE>(
-        &mut self,
-        rst: &mut RST,
-        delay: &mut DELAY,
-    ) -> Result<(), Error<(), PinE>>
-    where
-        RST: OutputPin<Error = PinE>,
-        DELAY: DelayMs<u8>,
-    {
-        rst.set_high().map_err(Error::Pin)?;
-        delay.delay_ms(1);
-        rst.set_low().map_err(Error::Pin)?;
-        delay.delay_ms(10);
-        rst.set_high().map_err(Error::Pin)
+    /// Turn a pixel on or off. A non-zero `value` is treated as on, `0` as off. If the X and Y
+    /// coordinates are out of the bounds of the display, this method call is a noop.
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
+        let (column, row) = match self.panel_position(x, y) {
+            Some((column, row)) => (column, self.ram_row(row)),
+            None => return,
+        };
+
+        let (display_width, _) = self.properties.get_size().dimensions();
+        let page = (row / 8) as usize;
+
+        let byte = &mut self.buffer.as_mut()[page * display_width as usize + column as usize];
+        let bit = 1 << (row % 8);
+
+        let previous = *byte;
+
+        if value == 0 {
+            *byte &= !bit;
+        } else {
+            *byte |= bit;
+        }
+
+        if *byte != previous {
+            self.mark_dirty(page, column, column + 1);
+        }
     }
 
-    /// Write out data to display
-    pub fn flush(&mut self) -> Result<(), DI::Error> {
-        let display_size = self.properties.get_size();
+    /// Whether the pixel at the given coordinates is on in the buffer, taking into account the
+    /// current rotation of the display. Returns `None` if the coordinates are out of the bounds of
+    /// the display.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<bool> {
+        let (column, row) = self.panel_position(x, y)?;
+        let row = self.ram_row(row);
 
-        // Ensure the display buffer is at the origin of the display before we send the full frame
-        // to prevent accidental offsets
-        let (display_width, display_height) = display_size.dimensions();
-        let column_offset = display_size.column_offset();
-        self.properties.set_draw_area(
-            (column_offset, 0),
-            (display_width + column_offset, display_height),
-        )?;
+        let (display_width, _) = self.properties.get_size().dimensions();
+        let byte =
+            self.buffer.as_ref()[(row / 8) as usize * display_width as usize + column as usize];
 
-        let length = (display_width as usize) * (display_height as usize) / 8;
+        Some(byte & (1 << (row % 8)) != 0)
+    }
 
-        self.properties.draw(&self.buffer[..length])
+    /// Raw framebuffer, in the layout of display RAM: one byte per column for each page of 8 rows,
+    /// with the least significant bit at the top. Each page is as wide as the display and pages
+    /// follow each other in order, starting at page 0 of display RAM. This ignores the rotation
+    /// and start line of the display.
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer.as_ref()
     }
 
-    /// Turn a pixel on or off. A non-zero `value` is treated as on, `0` as off. If the X and Y
-    /// coordinates are out of the bounds of the display, this method call is a noop.
-    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn get_dimensions(&self) -> (u8, u8) {
+        self.properties.get_dimensions()
+    }
+
+    /// Get the display RAM row shown on the first line of the panel
+    pub fn get_start_line(&self) -> u8 {
+        self.start_line
+    }
+
+    /// Map display coordinates to a column and row of the panel, taking into account the current
+    /// rotation of the display. Returns `None` if the coordinates are outside of the display.
+    fn panel_position(&self, x: u32, y: u32) -> Option<(u8, u8)> {
+        let (display_width, display_height) = self.properties.get_size().dimensions();
+
+        let (column, row) = match self.properties.get_rotation() {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (x, y),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (y, x),
+        };
+
+        if column >= display_width as u32 || row >= display_height as u32 {
+            return None;
+        }
+
+        Some((column as u8, row as u8))
+    }
+
+    /// Number of display RAM pages held in the buffer. This covers the whole display RAM if the
+    /// buffer is large enough, so that content scrolled into view by the start line is available.
+    fn pages(&self) -> usize {
+        let (display_width, display_height) = self.properties.get_size().dimensions();
+
+        if self.buffer.as_ref().len() / display_width as usize >= PAGES {
+            PAGES
+        } else {
+            (display_height / 8) as usize
+        }
+    }
+
+    /// Set of display RAM pages shown on the panel at the current start line, as a bit mask
+    fn visible_pages(&self) -> u8 {
+        let (_, display_height) = self.properties.get_size().dimensions();
+
+        (0..display_height).fold(0, |pages, row| pages | 1 << (self.ram_row(row) / 8))
+    }
+
+    /// First page shown on the panel that changed since the last flush, along with its changed
+    /// column span. Pages scrolled out of view stay marked as changed until they are shown again.
+    fn next_dirty_span(&self) -> Option<(usize, (u8, u8))> {
+        let visible = self.visible_pages();
+
+        (0..PAGES)
+            .filter(|page| visible & (1 << page) != 0)
+            .find_map(|page| self.dirty_span(page).map(|span| (page, span)))
+    }
+
+    /// Map a row of the panel to the display RAM row shown there, given the current start line
+    fn ram_row(&self, row: u8) -> u8 {
+        ((row as usize + self.start_line as usize) % (self.pages() * 8)) as u8
+    }
+
+    /// Mark the given column range of a page as changed since the last flush
+    fn mark_dirty(&mut self, page: usize, start: u8, end: u8) {
+        let (dirty_start, dirty_end) = &mut self.dirty[page];
+
+        *dirty_start = (*dirty_start).min(start);
+        *dirty_end = (*dirty_end).max(end);
+    }
+
+    /// Mark the whole display as changed since the last flush
+    fn mark_all_dirty(&mut self) {
         let (display_width, _) = self.properties.get_size().dimensions();
-        let display_rotation = self.properties.get_rotation();
 
-        let idx = match display_rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
-                if x >= display_width as u32 {
-                    return;
-                }
-                ((y as usize) / 8 * display_width as usize) + (x as usize)
-            }
+        for page in 0..self.pages() {
+            self.mark_dirty(page, 0, display_width);
+        }
+    }
 
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
-                if y >= display_width as u32 {
-                    return;
-                }
-                ((x as usize) / 8 * display_width as usize) + (y as usize)
-            }
+    /// Mark a span of a page as sent to the display, shrinking the changed span if it is only
+    /// partly covered from either end
+    fn mark_clean(&mut self, page: usize, start: u8, end: u8) {
+        let (dirty_start, dirty_end) = self.dirty[page];
+
+        if start <= dirty_start && dirty_end <= end {
+            self.dirty[page] = CLEAN;
+        } else if start <= dirty_start && dirty_start < end {
+            self.dirty[page].0 = end;
+        } else if start < dirty_end && dirty_end <= end {
+            self.dirty[page].1 = start;
+        }
+    }
+
+    /// Changed column span of a page, if any
+    fn dirty_span(&self, page: usize) -> Option<(u8, u8)> {
+        let (start, end) = self.dirty[page];
+
+        if start < end {
+            Some((start, end))
+        } else {
+            None
+        }
+    }
+
+    /// Set of pages, as a bit mask, and column span in the buffer covering the region of the
+    /// display between `start` and `end` (exclusive), or `None` if the region is empty
+    fn region_spans(&self, start: (u8, u8), end: (u8, u8)) -> Option<(u8, u8, u8)> {
+        let (display_width, display_height) = self.properties.get_size().dimensions();
+
+        let (x0, y0) = (start.0.min(end.0) as u32, start.1.min(end.1) as u32);
+        let (x1, y1) = (start.0.max(end.0) as u32, start.1.max(end.1) as u32);
+
+        if x0 == x1 || y0 == y1 {
+            return None;
+        }
+
+        // Map the inclusive corners onto the panel and clamp them to the display
+        let (first, last) = match self.properties.get_rotation() {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => ((x0, y0), (x1 - 1, y1 - 1)),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => ((y0, x0), (y1 - 1, x1 - 1)),
+        };
+
+        if first.0 >= display_width as u32 || first.1 >= display_height as u32 {
+            return None;
+        }
+
+        let last_column = last.0.min(display_width as u32 - 1);
+        let last_row = last.1.min(display_height as u32 - 1);
+
+        // The start line may wrap the region around the end of display RAM
+        let pages =
+            (first.1..=last_row).fold(0u8, |pages, row| pages | 1 << (self.ram_row(row as u8) / 8));
+
+        Some((pages, first.0 as u8, last_column as u8 + 1))
+    }
+
+    /// Start line to use for `line`, or `None` if the buffer doesn't hold the display RAM rows
+    /// that would be scrolled into view
+    fn checked_start_line(&self, line: u8) -> Option<u8> {
+        let line = line % (PAGES as u8 * 8);
+
+        if line == 0 || self.pages() == PAGES {
+            Some(line)
+        } else {
+            None
+        }
+    }
+
+    fn scrolled_start_line(&self, rows: i8) -> u8 {
+        (self.start_line as i16 + rows as i16).rem_euclid(PAGES as i16 * 8) as u8
+    }
+
+    /// Map a rectangle of display coordinates, already clipped to the display, to the columns and
+    /// rows of the panel it covers
+    #[cfg(feature = "graphics")]
+    fn panel_rect(&self, x: Range<u32>, y: Range<u32>) -> (Range<u8>, Range<u8>) {
+        let (columns, rows) = match self.properties.get_rotation() {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (x, y),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (y, x),
         };
 
-        if idx >= self.buffer.len() {
+        (
+            columns.start as u8..columns.end as u8,
+            rows.start as u8..rows.end as u8,
+        )
+    }
+
+    /// Turn a rectangle of the panel on or off, given as columns and rows of the panel
+    #[cfg(feature = "graphics")]
+    fn fill_panel(&mut self, columns: Range<u8>, rows: Range<u8>, on: bool) {
+        if columns.is_empty() || rows.is_empty() {
             return;
         }
 
-        let (byte, bit) = match display_rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
-                let byte =
-                    &mut self.buffer[((y as usize) / 8 * display_width as usize) + (x as usize)];
-                let bit = 1 << (y % 8);
+        // The start line may wrap the rows around the end of display RAM
+        let ram_rows = self.pages() * 8;
+        let start = self.ram_row(rows.start) as usize;
+        let end = start + rows.len();
+
+        self.fill_ram_rows(columns.clone(), start..end.min(ram_rows), on);
+
+        if end > ram_rows {
+            self.fill_ram_rows(columns, 0..end - ram_rows, on);
+        }
+    }
+
+    /// Turn a range of columns on or off in a contiguous range of display RAM rows, a page at a
+    /// time. Pages covered by the rows completely are filled a whole byte at a time, other pages are
+    /// masked.
+    #[cfg(feature = "graphics")]
+    fn fill_ram_rows(&mut self, columns: Range<u8>, rows: Range<usize>, on: bool) {
+        let (display_width, _) = self.properties.get_size().dimensions();
+
+        for page in rows.start / 8..=(rows.end - 1) / 8 {
+            let first = rows.start.max(page * 8) - page * 8;
+            let last = rows.end.min(page * 8 + 8) - page * 8;
+            let mask = ((1u16 << last) - (1u16 << first)) as u8;
 
-                (byte, bit)
+            let page_start = page * display_width as usize;
+            let bytes = &mut self.buffer.as_mut()
+                [page_start + columns.start as usize..page_start + columns.end as usize];
+            let update = |byte: u8| if on { byte | mask } else { byte & !mask };
+
+            // Only the span of bytes that actually change is written and marked dirty
+            let first = match bytes.iter().position(|&byte| update(byte) != byte) {
+                Some(first) => first,
+                None => continue,
+            };
+            let last = bytes
+                .iter()
+                .rposition(|&byte| update(byte) != byte)
+                .unwrap_or(first);
+
+            if mask == 0xFF {
+                bytes[first..=last].fill(if on { 0xFF } else { 0x00 });
+            } else {
+                for byte in &mut bytes[first..=last] {
+                    *byte = update(*byte);
+                }
             }
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
-                let byte =
-                    &mut self.buffer[((x as usize) / 8 * display_width as usize) + (y as usize)];
-                let bit = 1 << (x % 8);
 
-                (byte, bit)
+            self.mark_dirty(
+                page,
+                columns.start + first as u8,
+                columns.start + last as u8 + 1,
+            );
+        }
+    }
+
+    /// Merge rows packed by `fill_contiguous` into the buffer, a byte at a time. `group` is the
+    /// page the bytes belong to, or the panel column when `transposed`. Changed columns are added
+    /// to the spans in `changed`.
+    #[cfg(feature = "graphics")]
+    fn merge_packed(
+        &mut self,
+        group: u8,
+        transposed: bool,
+        columns: &Range<u8>,
+        bits: &[u8; RAM_COLUMNS],
+        masks: &[u8; RAM_COLUMNS],
+        changed: &mut [(u8, u8); PAGES],
+    ) {
+        let (display_width, _) = self.properties.get_size().dimensions();
+        let buffer = self.buffer.as_mut();
+
+        let mut merge = |page: u8, column: u8, index: usize| {
+            let mask = masks[index];
+            if mask == 0 {
+                return;
+            }
+
+            let byte = &mut buffer[page as usize * display_width as usize + column as usize];
+            let merged = if mask == 0xFF {
+                bits[index]
+            } else {
+                (*byte & !mask) | (bits[index] & mask)
+            };
+
+            if merged != *byte {
+                *byte = merged;
+
+                let (start, end) = &mut changed[page as usize];
+                *start = (*start).min(column);
+                *end = (*end).max(column + 1);
             }
         };
 
-        if value == 0 {
-            *byte &= !bit;
+        if transposed {
+            for page in 0..PAGES as u8 {
+                merge(page, group, page as usize);
+            }
         } else {
-            *byte |= bit;
+            for column in columns.clone() {
+                merge(group, column, column as usize);
+            }
+        }
+    }
+
+    /// Draw area start, end and buffer range covering columns `start..end` of a page
+    fn page_area(&self, page: usize, start: u8, end: u8) -> ((u8, u8), (u8, u8), Range<usize>) {
+        let (display_width, _) = self.properties.get_size().dimensions();
+        let column_offset = self.properties.get_column_offset();
+        let row = page as u8 * 8;
+        let page_start = page * display_width as usize;
+
+        (
+            (start + column_offset, row),
+            (end + column_offset, row + 8),
+            (page_start + start as usize)..(page_start + end as usize),
+        )
+    }
+}
+
+impl<DI, SIZE, BUF> GraphicsMode<DI, SIZE, BUF>
+where
+    DI: DisplayInterface,
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Write out data to display.
+    ///
+    /// Only the parts of the buffer that changed since the last flush are sent. Use
+    /// [`flush_region`](Self::flush_region) to send a specific region instead.
+    pub fn flush(&mut self) -> Result<(), DI::Error> {
+        while let Some((page, (start, end))) = self.next_dirty_span() {
+            self.flush_page(page, start, end)?;
         }
+
+        Ok(())
+    }
+
+    /// Write out the region of the buffer between the display coordinates `start` and `end`
+    /// (exclusive) to the display, regardless of whether it changed.
+    ///
+    /// The region is widened to whole pages of 8 rows. Coordinates take the current rotation of
+    /// the display into account.
+    pub fn flush_region(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), DI::Error> {
+        if let Some((pages, start, end)) = self.region_spans(start, end) {
+            for page in (0..PAGES).filter(|page| pages & (1 << page) != 0) {
+                self.flush_page(page, start, end)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write out part of the changes to the display, so that a flush can be interleaved with
+    /// time critical work without an async runtime.
+    ///
+    /// Each call sends at most `max_bytes` (but at least 1) bytes of the first page with changes.
+    /// Returns `true` once all changes have been sent, which is the same state as after a
+    /// [`flush`](Self::flush). Drawing between calls is fine, the new changes are picked up by
+    /// the following calls.
+    ///
+    /// ```rust
+    /// use sh1106::{prelude::*, Builder};
+    /// # let i2c = sh1106::test_helpers::I2cStub;
+    ///
+    /// let mut display: GraphicsMode<_> = Builder::new().connect_i2c(i2c).into();
+    ///
+    /// display.init().unwrap();
+    ///
+    /// // Send the buffer 32 bytes at a time
+    /// while !display.flush_step(32).unwrap() {
+    ///     // Do other work in between
+    /// }
+    /// ```
+    pub fn flush_step(&mut self, max_bytes: usize) -> Result<bool, DI::Error> {
+        if let Some((page, (start, end))) = self.next_dirty_span() {
+            let len = max_bytes.clamp(1, (end - start) as usize) as u8;
+
+            self.flush_page(page, start, start + len)?;
+        }
+
+        Ok(self.next_dirty_span().is_none())
+    }
+
+    fn flush_page(&mut self, page: usize, start: u8, end: u8) -> Result<(), DI::Error> {
+        let (area_start, area_end, range) = self.page_area(page, start, end);
+
+        self.properties.set_draw_area(area_start, area_end)?;
+        self.properties.draw(&self.buffer.as_ref()[range])?;
+
+        self.mark_clean(page, start, end);
+
+        Ok(())
     }
 
     /// Display is set up in column mode, i.e. a byte walks down a column of 8 pixels from
     /// column 0 on the left, to column _n_ on the right
     pub fn init(&mut self) -> Result<(), DI::Error> {
+        // Display RAM contents are undefined after initialisation
+        self.mark_all_dirty();
+        self.start_line = 0;
+
         self.properties.init_column_mode()
     }
 
-    /// Get display dimensions, taking into account the current rotation of the display
-    pub fn get_dimensions(&self) -> (u8, u8) {
-        self.properties.get_dimensions()
+    /// Initialise the display following the power-on sequence from the datasheet, see
+    /// [`DisplayProperties::init_column_mode_with_delay`]
+    pub fn init_with_delay<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        self.mark_all_dirty();
+        self.start_line = 0;
+
+        self.properties.init_column_mode_with_delay(delay)
+    }
+
+    /// Turn the display and charge pump off. The buffer and display RAM are kept, so drawing and
+    /// flushing can continue while asleep.
+    pub fn sleep(&mut self) -> Result<(), DI::Error> {
+        self.properties.sleep()
+    }
+
+    /// Turn the charge pump and display back on, see [`DisplayProperties::wake`]
+    pub fn wake(&mut self) -> Result<(), DI::Error> {
+        self.properties.wake()
+    }
+
+    /// Turn the charge pump and display back on, waiting for the charge pump to settle, see
+    /// [`DisplayProperties::wake_with_delay`]
+    pub fn wake_with_delay<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        self.properties.wake_with_delay(delay)
     }
 
     /// Set the display rotation
+    ///
+    /// The buffer isn't changed, but the whole buffer is sent again on the next flush as the panel
+    /// may show a different range of display RAM columns in the new rotation.
     pub fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), DI::Error> {
+        self.mark_all_dirty();
         self.properties.set_rotation(rot)
     }
 
+    /// Mirror the display horizontally and/or vertically, independently of its rotation. Axes are
+    /// those of the rotated display.
+    ///
+    /// Like [`set_rotation`](Self::set_rotation), the whole buffer is sent again on the next flush.
+    pub fn set_flip(&mut self, flip_x: bool, flip_y: bool) -> Result<(), DI::Error> {
+        self.mark_all_dirty();
+        self.properties.set_flip(flip_x, flip_y)
+    }
+
     /// Set the display contrast
     pub fn set_contrast(&mut self, contrast: u8) -> Result<(), DI::Error> {
         self.properties.set_contrast(contrast)
     }
 }
 
+#[cfg(feature = "async")]
+impl<DI, SIZE, BUF> GraphicsMode<DI, SIZE, BUF>
+where
+    DI: AsyncDisplayInterface,
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Asynchronous version of [`init`](Self::init)
+    pub async fn init_async(&mut self) -> Result<(), DI::Error> {
+        self.mark_all_dirty();
+        self.start_line = 0;
+
+        self.properties.init_column_mode_async().await
+    }
+
+    /// Asynchronous version of [`flush`](Self::flush)
+    pub async fn flush_async(&mut self) -> Result<(), DI::Error> {
+        while let Some((page, (start, end))) = self.next_dirty_span() {
+            self.flush_page_async(page, start, end).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Asynchronous version of [`flush_region`](Self::flush_region)
+    pub async fn flush_region_async(
+        &mut self,
+        start: (u8, u8),
+        end: (u8, u8),
+    ) -> Result<(), DI::Error> {
+        if let Some((pages, start, end)) = self.region_spans(start, end) {
+            for page in (0..PAGES).filter(|page| pages & (1 << page) != 0) {
+                self.flush_page_async(page, start, end).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn flush_page_async(&mut self, page: usize, start: u8, end: u8) -> Result<(), DI::Error> {
+        let (area_start, area_end, range) = self.page_area(page, start, end);
+
+        self.properties
+            .set_draw_area_async(area_start, area_end)
+            .await?;
+        self.properties
+            .draw_async(&self.buffer.as_ref()[range])
+            .await?;
+
+        self.mark_clean(page, start, end);
+
+        Ok(())
+    }
+
+    /// Asynchronous version of [`init_with_delay`](Self::init_with_delay)
+    pub async fn init_with_delay_async<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: embedded_hal_async::delay::DelayNs,
+    {
+        self.mark_all_dirty();
+        self.start_line = 0;
+
+        self.properties
+            .init_column_mode_with_delay_async(delay)
+            .await
+    }
+
+    /// Asynchronous version of [`sleep`](Self::sleep)
+    pub async fn sleep_async(&mut self) -> Result<(), DI::Error> {
+        self.properties.sleep_async().await
+    }
+
+    /// Asynchronous version of [`wake`](Self::wake)
+    pub async fn wake_async(&mut self) -> Result<(), DI::Error> {
+        self.properties.wake_async().await
+    }
+
+    /// Asynchronous version of [`wake_with_delay`](Self::wake_with_delay)
+    pub async fn wake_with_delay_async<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: embedded_hal_async::delay::DelayNs,
+    {
+        self.properties.wake_with_delay_async(delay).await
+    }
+
+    /// Asynchronous version of [`set_rotation`](Self::set_rotation)
+    pub async fn set_rotation_async(&mut self, rot: DisplayRotation) -> Result<(), DI::Error> {
+        self.mark_all_dirty();
+        self.properties.set_rotation_async(rot).await
+    }
+
+    /// Asynchronous version of [`set_flip`](Self::set_flip)
+    pub async fn set_flip_async(&mut self, flip_x: bool, flip_y: bool) -> Result<(), DI::Error> {
+        self.mark_all_dirty();
+        self.properties.set_flip_async(flip_x, flip_y).await
+    }
+
+    /// Asynchronous version of [`set_contrast`](Self::set_contrast)
+    pub async fn set_contrast_async(&mut self, contrast: u8) -> Result<(), DI::Error> {
+        self.properties.set_contrast_async(contrast).await
+    }
+}
+
+impl<DI, SIZE, BUF, CommE, PinE> GraphicsMode<DI, SIZE, BUF>
+where
+    DI: DisplayInterface<Error = Error<CommE, PinE>>,
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Send a single command to the display, see
+    /// [`DisplayProperties::send_command`](crate::properties::DisplayProperties::send_command).
+    ///
+    /// Commands that change state tracked by the driver, like the start line or rotation, should
+    /// be sent through the dedicated methods instead so the buffer stays in sync with the display.
+    pub fn send_command(&mut self, command: Command) -> Result<(), DI::Error> {
+        self.properties.send_command(command)
+    }
+
+    /// Set the display RAM row shown on the first line of the panel, from 0-63.
+    ///
+    /// This scrolls the whole display vertically in hardware without sending the buffer again.
+    /// Rows scrolled off one edge reappear at the other. Coordinates passed to
+    /// [`set_pixel`](Self::set_pixel) and the `DrawTarget` implementation stay relative to the
+    /// top left corner of the panel, so newly drawn content always appears where it is drawn.
+    /// Call [`flush`](Self::flush) afterwards to send rows scrolled into view that changed while
+    /// they were hidden.
+    ///
+    /// Returns [`Error::InvalidArgument`] for any line but 0 if the buffer doesn't hold all 64
+    /// rows of display RAM, which is the case for panels less than 64 pixels high combined with
+    /// an exactly sized buffer like [`Size128x32`](crate::displaysize::Size128x32).
+    pub fn set_start_line(&mut self, line: u8) -> Result<(), DI::Error> {
+        self.start_line = self
+            .checked_start_line(line)
+            .ok_or(Error::InvalidArgument)?;
+
+        self.properties.set_start_line(self.start_line)
+    }
+
+    /// Scroll the display content up by `rows` rows, or down for negative values. See
+    /// [`set_start_line`](Self::set_start_line).
+    pub fn scroll(&mut self, rows: i8) -> Result<(), DI::Error> {
+        self.set_start_line(self.scrolled_start_line(rows))
+    }
+
+    /// Reset the display by pulsing its reset pin, see
+    /// [`DisplayProperties::reset`](crate::properties::DisplayProperties::reset)
+    pub fn reset<RST, DELAY>(
+        &mut self,
+        rst: &mut RST,
+        delay: &mut DELAY,
+    ) -> Result<(), Error<CommE, RST::Error>>
+    where
+        RST: OutputPin,
+        DELAY: DelayMs<u8>,
+    {
+        self.properties.reset(rst, delay)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<DI, SIZE, BUF, CommE, PinE> GraphicsMode<DI, SIZE, BUF>
+where
+    DI: AsyncDisplayInterface<Error = Error<CommE, PinE>>,
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Asynchronous version of [`send_command`](Self::send_command)
+    pub async fn send_command_async(&mut self, command: Command) -> Result<(), DI::Error> {
+        self.properties.send_command_async(command).await
+    }
+
+    /// Asynchronous version of [`set_start_line`](Self::set_start_line)
+    pub async fn set_start_line_async(&mut self, line: u8) -> Result<(), DI::Error> {
+        self.start_line = self
+            .checked_start_line(line)
+            .ok_or(Error::InvalidArgument)?;
+
+        self.properties.set_start_line_async(self.start_line).await
+    }
+
+    /// Asynchronous version of [`scroll`](Self::scroll)
+    pub async fn scroll_async(&mut self, rows: i8) -> Result<(), DI::Error> {
+        self.set_start_line_async(self.scrolled_start_line(rows))
+            .await
+    }
+}
+
 #[cfg(feature = "graphics")]
 use embedded_graphics_core::{
     draw_target::DrawTarget,
     geometry::Size,
-    geometry::{Dimensions, OriginDimensions},
+    geometry::{Dimensions, OriginDimensions, Point},
+    image::GetPixel,
     pixelcolor::BinaryColor,
+    primitives::Rectangle,
     Pixel,
 };
 
 #[cfg(feature = "graphics")]
-impl<DI> DrawTarget for GraphicsMode<DI>
+impl<DI, SIZE, BUF> DrawTarget for GraphicsMode<DI, SIZE, BUF>
 where
-    DI: DisplayInterface,
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
 {
     type Color = BinaryColor;
     type Error = core::convert::Infallible;
@@ -226,12 +884,146 @@ where
 
         Ok(())
     }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let drawable = area.intersection(&self.bounding_box());
+
+        if drawable.is_zero_sized() {
+            return Ok(());
+        }
+
+        let (x, y) = (drawable.top_left.x as u32, drawable.top_left.y as u32);
+        let (columns, _) = self.panel_rect(x..x + drawable.size.width, y..y + drawable.size.height);
+        let transposed = matches!(
+            self.properties.get_rotation(),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270
+        );
+
+        // Colors are packed into bytes until drawing moves on to another page, or in the 90 and
+        // 270 degree rotations, to another column of the panel. Bits and masks of drawn rows are
+        // indexed by column, or by page in those rotations.
+        let mut packed = None;
+        let mut bits = [0u8; RAM_COLUMNS];
+        let mut masks = [0u8; RAM_COLUMNS];
+        let mut changed = [CLEAN; PAGES];
+
+        let skip = (drawable.top_left.x - area.top_left.x) as usize;
+        let mut colors = colors.into_iter();
+
+        for row in area.rows() {
+            let mut colors = colors.by_ref().take(area.size.width as usize);
+
+            if drawable.rows().contains(&row) {
+                for (column, color) in drawable.columns().zip(colors.by_ref().skip(skip)) {
+                    let (column, row) = if transposed {
+                        (row as u8, column as u8)
+                    } else {
+                        (column as u8, row as u8)
+                    };
+                    let row = self.ram_row(row);
+                    let page = row / 8;
+                    let (group, index) = if transposed {
+                        (column, page)
+                    } else {
+                        (page, column)
+                    };
+
+                    if packed != Some(group) {
+                        if let Some(group) = packed {
+                            self.merge_packed(
+                                group,
+                                transposed,
+                                &columns,
+                                &bits,
+                                &masks,
+                                &mut changed,
+                            );
+                        }
+
+                        packed = Some(group);
+                        masks = [0; RAM_COLUMNS];
+                    }
+
+                    let bit = 1 << (row % 8);
+                    if color.is_on() {
+                        bits[index as usize] |= bit;
+                    } else {
+                        bits[index as usize] &= !bit;
+                    }
+                    masks[index as usize] |= bit;
+                }
+            }
+
+            // Skip colors of the row outside of the display
+            colors.for_each(drop);
+        }
+
+        if let Some(group) = packed {
+            self.merge_packed(group, transposed, &columns, &bits, &masks, &mut changed);
+        }
+
+        for (page, &(start, end)) in changed.iter().enumerate() {
+            if start < end {
+                self.mark_dirty(page, start, end);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+
+        let (x, y) = (area.top_left.x as u32, area.top_left.y as u32);
+        let (columns, rows) = self.panel_rect(x..x + area.size.width, y..y + area.size.height);
+
+        self.fill_panel(columns, rows, color.is_on());
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let (display_width, _) = self.properties.get_size().dimensions();
+        let len = self.pages() * display_width as usize;
+        let value = if color.is_on() { 0xFF } else { 0x00 };
+
+        self.buffer.as_mut()[..len].fill(value);
+        self.mark_all_dirty();
+
+        Ok(())
+    }
 }
 
 #[cfg(feature = "graphics")]
-impl<DI> OriginDimensions for GraphicsMode<DI>
+impl<DI, SIZE, BUF> GetPixel for GraphicsMode<DI, SIZE, BUF>
 where
-    DI: DisplayInterface,
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
+{
+    type Color = BinaryColor;
+
+    fn pixel(&self, p: Point) -> Option<Self::Color> {
+        if p.x < 0 || p.y < 0 {
+            return None;
+        }
+
+        self.get_pixel(p.x as u32, p.y as u32)
+            .map(BinaryColor::from)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DI, SIZE, BUF> OriginDimensions for GraphicsMode<DI, SIZE, BUF>
+where
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
 {
     fn size(&self) -> Size {
         let (w, h) = self.get_dimensions();
@@ -239,3 +1031,621 @@ where
         Size::new(w.into(), h.into())
     }
 }
+
+#[cfg(feature = "std")]
+impl<DI, SIZE, BUF> GraphicsMode<DI, SIZE, BUF>
+where
+    SIZE: PanelSize,
+    BUF: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Take a snapshot of the buffer as seen on the display, taking into account the current
+    /// rotation and start line. See the [`snapshot`](crate::snapshot) module for comparing it
+    /// against golden images.
+    pub fn snapshot(&self) -> Image {
+        let (width, height) = self.get_dimensions();
+
+        Image::from_fn(width as u32, height as u32, |x, y| {
+            self.get_pixel(x, y).unwrap_or(false)
+        })
+    }
+
+    /// Encode the buffer as a binary PBM file, as seen on the display
+    pub fn to_pbm(&self) -> std::vec::Vec<u8> {
+        self.snapshot().to_pbm()
+    }
+
+    /// Encode the buffer as a 1 bit PNG file, as seen on the display
+    pub fn to_png(&self) -> std::vec::Vec<u8> {
+        self.snapshot().to_png()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::Builder,
+        displaysize::{CustomSize, DisplaySize, Size128x32},
+        test_helpers::{I2cStub, PinId, Recorder, Transaction},
+    };
+    #[cfg(feature = "graphics")]
+    use embedded_graphics_core::primitives::PointsIter;
+
+    #[test]
+    fn only_changed_spans_are_dirty_after_flush() {
+        let mut display: GraphicsMode<_> = Builder::new().connect_i2c(I2cStub).into();
+
+        display.flush().unwrap();
+        assert!((0..PAGES).all(|page| display.dirty_span(page).is_none()));
+
+        display.set_pixel(10, 20, 1);
+        display.set_pixel(30, 22, 1);
+        display.set_pixel(5, 63, 1);
+
+        assert_eq!(display.dirty_span(2), Some((10, 31)));
+        assert_eq!(display.dirty_span(7), Some((5, 6)));
+        assert_eq!(display.dirty_span(0), None);
+
+        display.flush().unwrap();
+        assert!((0..PAGES).all(|page| display.dirty_span(page).is_none()));
+    }
+
+    #[test]
+    fn unchanged_pixels_are_not_dirty() {
+        let mut display: GraphicsMode<_> = Builder::new().connect_i2c(I2cStub).into();
+
+        display.flush().unwrap();
+        display.set_pixel(10, 20, 0);
+
+        assert_eq!(display.dirty_span(2), None);
+    }
+
+    #[test]
+    fn flush_region_cleans_covered_pages() {
+        let mut display: GraphicsMode<_> = Builder::new().connect_i2c(I2cStub).into();
+
+        display.flush().unwrap();
+        display.set_pixel(10, 20, 1);
+        display.set_pixel(10, 40, 1);
+
+        display.flush_region((0, 16), (32, 24)).unwrap();
+
+        assert_eq!(display.dirty_span(2), None);
+        assert_eq!(display.dirty_span(5), Some((10, 11)));
+    }
+
+    #[test]
+    fn fixed_size_buffer_is_bounded_by_panel() {
+        let mut display: GraphicsMode<_, Size128x32> = Builder::new().connect_i2c(I2cStub).into();
+
+        display.flush().unwrap();
+        display.set_pixel(127, 31, 1);
+        display.set_pixel(0, 32, 1);
+
+        assert_eq!(display.buffer.len(), 128 * 32 / 8);
+        assert_eq!(display.buffer[3 * 128 + 127], 0x80);
+        assert_eq!(display.dirty_span(3), Some((127, 128)));
+        display.flush().unwrap();
+    }
+
+    #[test]
+    fn custom_size_is_used_for_layout() {
+        let size = CustomSize::new(72, 40).with_column_offset(30);
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_size(DisplaySize::Custom(size))
+            .connect_i2c(I2cStub)
+            .into();
+
+        assert_eq!(display.get_dimensions(), (72, 40));
+        assert_eq!(display.dirty_span(4), Some((0, 72)));
+        // The rest of display RAM is kept as well so it can be scrolled into view
+        assert_eq!(display.dirty_span(7), Some((0, 72)));
+
+        display.set_pixel(71, 39, 1);
+        assert_eq!(display.buffer[4 * 72 + 71], 0x80);
+        assert_eq!(
+            display.page_area(4, 71, 72),
+            ((101, 32), (102, 40), 359..360)
+        );
+    }
+
+    #[test]
+    fn region_is_widened_to_pages() {
+        let display: GraphicsMode<_> = Builder::new().connect_i2c(I2cStub).into();
+
+        assert_eq!(
+            display.region_spans((4, 3), (20, 17)),
+            Some((0b0000_0111, 4, 20))
+        );
+        assert_eq!(display.region_spans((4, 3), (4, 17)), None);
+        assert_eq!(
+            display.region_spans((100, 60), (200, 200)),
+            Some((0b1000_0000, 100, 128))
+        );
+    }
+
+    #[test]
+    fn drawing_follows_start_line() {
+        let mut display: GraphicsMode<_> = Builder::new().connect_i2c(I2cStub).into();
+
+        display.flush().unwrap();
+        display.scroll(-4).unwrap();
+        assert_eq!(display.get_start_line(), 60);
+
+        // Panel row 0 now shows display RAM row 60
+        display.set_pixel(0, 0, 1);
+        assert_eq!(display.buffer[7 * 128], 1 << 4);
+        assert_eq!(display.dirty_span(7), Some((0, 1)));
+
+        // A region crossing the end of display RAM wraps around to the first page
+        assert_eq!(
+            display.region_spans((0, 0), (8, 8)),
+            Some((0b1000_0001, 0, 8))
+        );
+
+        display.scroll(10).unwrap();
+        assert_eq!(display.get_start_line(), 6);
+    }
+
+    #[test]
+    fn small_panels_scroll_through_all_of_display_ram() {
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_size(DisplaySize::Display128x32)
+            .connect_i2c(I2cStub)
+            .into();
+
+        assert_eq!(display.pages(), 8);
+
+        // Panel row 31 shows display RAM row 71 % 64 = 7
+        display.set_start_line(40).unwrap();
+        display.set_pixel(0, 31, 1);
+        assert_eq!(display.buffer[0], 1 << 7);
+    }
+
+    #[test]
+    fn exact_small_buffer_cannot_scroll() {
+        let mut display: GraphicsMode<_, Size128x32> = Builder::new().connect_i2c(I2cStub).into();
+
+        assert!(display.set_start_line(0).is_ok());
+        assert!(matches!(
+            display.set_start_line(8),
+            Err(Error::InvalidArgument)
+        ));
+        assert!(matches!(display.scroll(1), Err(Error::InvalidArgument)));
+        assert_eq!(display.get_start_line(), 0);
+    }
+
+    #[test]
+    fn only_visible_pages_are_flushed() {
+        let recorder = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_size(DisplaySize::Display128x32)
+            .connect_i2c(recorder.i2c())
+            .into();
+
+        let data_bytes = |recorder: &Recorder| {
+            recorder
+                .log()
+                .transactions()
+                .filter_map(|transaction| match transaction {
+                    Transaction::I2cWrite { bytes, .. } if bytes[0] == 0x40 => {
+                        Some(bytes.len() - 1)
+                    }
+                    _ => None,
+                })
+                .sum::<usize>()
+        };
+
+        display.flush().unwrap();
+        assert_eq!(data_bytes(&recorder), 128 * 32 / 8);
+
+        // Rows scrolled into view are sent by the next flush
+        display.scroll(32).unwrap();
+        recorder.clear();
+        display.flush().unwrap();
+        assert_eq!(data_bytes(&recorder), 128 * 32 / 8);
+
+        recorder.clear();
+        display.flush().unwrap();
+        assert_eq!(data_bytes(&recorder), 0);
+    }
+
+    #[test]
+    fn pixels_are_read_back_through_rotation() {
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_rotation(DisplayRotation::Rotate270)
+            .connect_i2c(I2cStub)
+            .into();
+
+        display.set_pixel(5, 9, 1);
+
+        assert_eq!(display.get_pixel(5, 9), Some(true));
+        assert_eq!(display.get_pixel(9, 5), Some(false));
+        assert_eq!(display.get_pixel(64, 0), None);
+        assert_eq!(display.buffer()[9], 1 << 5);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn get_pixel_follows_start_line() {
+        let mut display: GraphicsMode<_> = Builder::new().connect_i2c(I2cStub).into();
+
+        display.set_start_line(20).unwrap();
+        display.set_pixel(3, 50, 1);
+
+        assert_eq!(display.pixel(Point::new(3, 50)), Some(BinaryColor::On));
+        assert_eq!(display.pixel(Point::new(3, 49)), Some(BinaryColor::Off));
+        assert_eq!(display.pixel(Point::new(-1, 50)), None);
+        assert_eq!(display.pixel(Point::new(3, 64)), None);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn fills_match_pixel_drawing() {
+        let areas = [
+            Rectangle::new(Point::new(3, 5), Size::new(20, 17)),
+            Rectangle::new(Point::new(-4, -2), Size::new(10, 40)),
+            Rectangle::new(Point::new(100, 56), Size::new(60, 30)),
+            Rectangle::new(Point::new(8, 16), Size::new(16, 24)),
+            Rectangle::new(Point::new(-3, 30), Size::new(140, 9)),
+        ];
+        let rotations = [
+            DisplayRotation::Rotate0,
+            DisplayRotation::Rotate90,
+            DisplayRotation::Rotate180,
+            DisplayRotation::Rotate270,
+        ];
+
+        for &size in &[DisplaySize::Display128x64, DisplaySize::Display128x32] {
+            for &rotation in &rotations {
+                for &start_line in &[0, 60] {
+                    let builder = Builder::new()
+                        .with_size(size)
+                        .with_rotation(rotation)
+                        .with_flip(true, false);
+                    let mut filled: GraphicsMode<_> = builder.connect_i2c(I2cStub).into();
+                    let mut drawn: GraphicsMode<_> = builder.connect_i2c(I2cStub).into();
+
+                    for display in [&mut filled, &mut drawn] {
+                        display.set_start_line(start_line).unwrap();
+                        display.flush().unwrap();
+                    }
+
+                    for (i, area) in areas.iter().enumerate() {
+                        let color = BinaryColor::from(i != 1);
+                        let colors = area
+                            .points()
+                            .map(|p| BinaryColor::from((p.x + 2 * p.y) % 3 == 0));
+
+                        filled.fill_solid(area, color).unwrap();
+                        drawn
+                            .draw_iter(area.points().map(|p| Pixel(p, color)))
+                            .unwrap();
+                        assert_eq!(filled.buffer(), drawn.buffer());
+                        assert_eq!(filled.dirty, drawn.dirty);
+
+                        filled.fill_contiguous(area, colors.clone()).unwrap();
+                        drawn
+                            .draw_iter(area.points().zip(colors).map(|(p, c)| Pixel(p, c)))
+                            .unwrap();
+                        assert_eq!(filled.buffer(), drawn.buffer());
+                        assert_eq!(filled.dirty, drawn.dirty);
+                    }
+
+                    // Fewer colors than the area holds leave the rest of it untouched
+                    let area = areas[0];
+                    let colors = area.points().take(30).map(|_| BinaryColor::Off);
+                    filled.fill_contiguous(&area, colors.clone()).unwrap();
+                    drawn
+                        .draw_iter(area.points().zip(colors).map(|(p, c)| Pixel(p, c)))
+                        .unwrap();
+                    assert_eq!(filled.buffer(), drawn.buffer());
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn aligned_fills_write_whole_bytes() {
+        let mut display: GraphicsMode<_> = Builder::new().connect_i2c(I2cStub).into();
+
+        display.flush().unwrap();
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(4, 8), Size::new(8, 16)),
+                BinaryColor::On,
+            )
+            .unwrap();
+
+        assert_eq!(display.buffer()[128 + 4..128 + 12], [0xFF; 8]);
+        assert_eq!(display.buffer()[2 * 128 + 4..2 * 128 + 12], [0xFF; 8]);
+        assert_eq!(display.dirty_span(1), Some((4, 12)));
+        assert_eq!(display.dirty_span(3), None);
+
+        display.flush().unwrap();
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(4, 8), Size::new(8, 8)),
+                BinaryColor::On,
+            )
+            .unwrap();
+        assert_eq!(display.dirty_span(1), None);
+
+        DrawTarget::clear(&mut display, BinaryColor::On).unwrap();
+        assert!(display.buffer()[..8 * 128].iter().all(|&byte| byte == 0xFF));
+        assert_eq!(display.dirty_span(7), Some((0, 128)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn snapshot_is_rotated() {
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_rotation(DisplayRotation::Rotate90)
+            .connect_i2c(I2cStub)
+            .into();
+
+        display.set_pixel(2, 100, 1);
+        let snapshot = display.snapshot();
+
+        assert_eq!(snapshot.dimensions(), (64, 128));
+        assert!(snapshot.pixel(2, 100));
+        assert_eq!(display.buffer[100], 1 << 2);
+    }
+
+    #[test]
+    fn flush_region_frames_spi_writes() {
+        let recorder = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new()
+            .connect_spi(
+                recorder.spi(),
+                recorder.pin(PinId::Dc),
+                recorder.pin(PinId::Cs),
+            )
+            .into();
+
+        display.set_pixel(1, 0, 1);
+        recorder.clear();
+        display.flush_region((0, 0), (2, 8)).unwrap();
+
+        let log = recorder.log();
+        let mut writes = log
+            .transactions()
+            .filter_map(|transaction| match transaction {
+                Transaction::SpiWrite { dc, cs, bytes } => Some((dc, cs, bytes)),
+                _ => None,
+            });
+
+        // Page and column address, with the 2 column offset of 128x64 panels, as commands
+        assert_eq!(writes.next(), Some((false, false, &[0xB0][..])));
+        assert_eq!(writes.next(), Some((false, false, &[0x02][..])));
+        assert_eq!(writes.next(), Some((false, false, &[0x10][..])));
+        // Then the two columns of page 0 as data
+        assert_eq!(writes.next(), Some((true, false, &[0x00, 0x01][..])));
+
+        assert_eq!(
+            log.transactions().last(),
+            Some(Transaction::Pin {
+                pin: PinId::Cs,
+                high: true
+            })
+        );
+    }
+
+    #[test]
+    fn caller_buffer_is_drawn_into() {
+        let mut storage = [0xFFu8; 128 * 32 / 8];
+        let properties = Builder::new()
+            .with_size(DisplaySize::Display128x32)
+            .connect_i2c(I2cStub)
+            .release();
+        let mut display: GraphicsMode<_, DynamicSize, _> =
+            GraphicsMode::with_buffer(properties, &mut storage[..]);
+
+        assert!(display.buffer().iter().all(|&byte| byte == 0));
+
+        display.set_pixel(3, 9, 1);
+        display.flush().unwrap();
+        assert_eq!(display.get_pixel(3, 9), Some(true));
+
+        let (_, storage) = display.release_buffer();
+        assert_eq!(storage[128 + 3], 0b10);
+    }
+
+    #[test]
+    fn caller_buffer_is_shared_between_displays() {
+        let mut storage = [0u8; 128 * 64 / 8];
+        let first: GraphicsMode<_, DynamicSize, _> = GraphicsMode::with_buffer(
+            Builder::new().connect_i2c(I2cStub).release(),
+            &mut storage[..],
+        );
+        let (first, storage) = first.release_buffer();
+
+        let mut second: GraphicsMode<_, DynamicSize, _> =
+            GraphicsMode::with_buffer(Builder::new().connect_i2c(I2cStub).release(), storage);
+        second.set_pixel(0, 0, 1);
+        let (_, storage) = second.release_buffer();
+
+        let first: GraphicsMode<_, DynamicSize, _> = GraphicsMode::with_buffer(first, storage);
+        assert_eq!(first.get_pixel(0, 0), Some(false));
+    }
+
+    #[test]
+    #[should_panic(expected = "buffer is too small")]
+    fn caller_buffer_must_fit_display() {
+        let mut storage = [0u8; 128 * 32 / 8];
+
+        let _: GraphicsMode<_, DynamicSize, _> = GraphicsMode::with_buffer(
+            Builder::new().connect_i2c(I2cStub).release(),
+            &mut storage[..],
+        );
+    }
+
+    #[test]
+    fn fixed_size_accepts_matching_or_default_builder_size() {
+        let display: GraphicsMode<_, Size128x32> = Builder::new()
+            .with_size(DisplaySize::Display128x32)
+            .connect_i2c(I2cStub)
+            .into();
+        assert_eq!(display.get_dimensions(), (128, 32));
+
+        let display: GraphicsMode<_, Size128x32> = Builder::new().connect_i2c(I2cStub).into();
+        assert_eq!(display.get_dimensions(), (128, 32));
+    }
+
+    #[test]
+    #[should_panic(expected = "panel size of the mode doesn't match")]
+    fn fixed_size_rejects_other_builder_size() {
+        let _: GraphicsMode<_, Size128x32> = Builder::new()
+            .with_size(DisplaySize::Display128x64)
+            .connect_i2c(I2cStub)
+            .into();
+    }
+
+    #[test]
+    #[should_panic(expected = "panel size of the mode doesn't match")]
+    fn caller_buffer_rejects_other_builder_size() {
+        let mut storage = [0u8; 132 * 64 / 8];
+
+        let _: GraphicsMode<_, Size128x32, _> = GraphicsMode::with_buffer(
+            Builder::new()
+                .with_size(DisplaySize::Display132x64)
+                .connect_i2c(I2cStub)
+                .release(),
+            &mut storage[..],
+        );
+    }
+
+    #[test]
+    fn flush_step_sends_bounded_chunks() {
+        let recorder = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new().connect_i2c(recorder.i2c()).into();
+
+        display.flush().unwrap();
+        display.set_pixel(0, 0, 1);
+        display.set_pixel(9, 0, 1);
+        display.set_pixel(4, 20, 1);
+        recorder.clear();
+
+        let mut steps = 0;
+        while !display.flush_step(4).unwrap() {
+            steps += 1;
+        }
+
+        // Columns 0-9 of page 0 in three chunks, then column 4 of page 2
+        assert_eq!(steps, 3);
+
+        let log = recorder.log();
+        let data = log
+            .transactions()
+            .filter_map(|transaction| match transaction {
+                Transaction::I2cWrite { bytes, .. } if bytes[0] == 0x40 => Some(bytes.len() - 1),
+                _ => None,
+            });
+        assert!(data.eq([4, 4, 2, 1].iter().copied()));
+        drop(log);
+
+        recorder.clear();
+        assert!(display.flush_step(4).unwrap());
+        assert!(recorder.log().is_empty());
+    }
+
+    #[test]
+    fn flush_step_picks_up_changes_between_steps() {
+        let mut display: GraphicsMode<_> = Builder::new().connect_i2c(I2cStub).into();
+
+        display.flush().unwrap();
+        display.set_pixel(10, 0, 1);
+        display.set_pixel(19, 0, 1);
+
+        assert!(!display.flush_step(5).unwrap());
+        assert_eq!(display.dirty_span(0), Some((15, 20)));
+
+        display.set_pixel(12, 0, 1);
+        assert_eq!(display.dirty_span(0), Some((12, 20)));
+
+        assert!(!display.flush_step(5).unwrap());
+        assert!(display.flush_step(5).unwrap());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn init_async_matches_blocking() {
+        use crate::test_helpers::{block_on, DelayStub};
+
+        let blocking = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_reset(blocking.pin(PinId::Reset), DelayStub)
+            .connect_i2c(blocking.i2c())
+            .into();
+        display.init().unwrap();
+
+        let asynchronous = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new()
+            .with_reset(asynchronous.pin(PinId::Reset), DelayStub)
+            .connect_i2c_async(asynchronous.i2c())
+            .into();
+        block_on(display.init_async()).unwrap();
+
+        assert!(!blocking.log().is_empty());
+        assert!(blocking
+            .log()
+            .transactions()
+            .eq(asynchronous.log().transactions()));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn flush_async_matches_blocking() {
+        use crate::test_helpers::block_on;
+
+        let blocking = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new().connect_i2c(blocking.i2c()).into();
+        display.flush().unwrap();
+        display.set_pixel(10, 20, 1);
+        display.set_pixel(100, 60, 1);
+        display.flush().unwrap();
+
+        let asynchronous = Recorder::new();
+        let mut display: GraphicsMode<_> =
+            Builder::new().connect_i2c_async(asynchronous.i2c()).into();
+        block_on(display.flush_async()).unwrap();
+        display.set_pixel(10, 20, 1);
+        display.set_pixel(100, 60, 1);
+        block_on(display.flush_async()).unwrap();
+
+        assert!(!blocking.log().is_empty());
+        assert!(blocking
+            .log()
+            .transactions()
+            .eq(asynchronous.log().transactions()));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn set_rotation_async_matches_blocking() {
+        use crate::test_helpers::block_on;
+
+        let blocking = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new()
+            .connect_spi_device(blocking.spi(), blocking.pin(PinId::Dc))
+            .into();
+        display.set_rotation(DisplayRotation::Rotate180).unwrap();
+        display.set_pixel(3, 5, 1);
+        display.flush().unwrap();
+
+        let asynchronous = Recorder::new();
+        let mut display: GraphicsMode<_> = Builder::new()
+            .connect_spi_async(asynchronous.spi(), asynchronous.pin(PinId::Dc))
+            .into();
+        block_on(display.set_rotation_async(DisplayRotation::Rotate180)).unwrap();
+        display.set_pixel(3, 5, 1);
+        block_on(display.flush_async()).unwrap();
+
+        assert!(!blocking.log().is_empty());
+        assert!(blocking
+            .log()
+            .transactions()
+            .eq(asynchronous.log().transactions()));
+    }
+}
@@ -2,9 +2,31 @@
 //!
 //! This driver can be used in different modes. A mode defines how the driver will behave, and what
 //! methods it exposes. Look at the modes below for more information on what they expose.
+//!
+//! Every mode can be torn down again with [`DisplayModeTrait::release`], which returns the
+//! [`DisplayProperties`](crate::properties::DisplayProperties). Releasing those in turn returns the
+//! interface, which hands back the underlying peripherals and pins:
+//!
+//! ```rust
+//! use sh1106::{prelude::*, Builder};
+//! # let spi = sh1106::test_helpers::SpiStub;
+//! # let dc = sh1106::test_helpers::PinStub;
+//! # let cs = sh1106::test_helpers::PinStub;
+//!
+//! let display: GraphicsMode<_> = Builder::new().connect_spi(spi, dc, cs).into();
+//!
+//! let (spi, dc, cs) = display.release().release().release();
+//! ```
+//!
+//! [`DisplayModeTrait::release`]: displaymode::DisplayModeTrait::release
 
+#[cfg(feature = "graphics")]
+pub mod direct;
 pub mod displaymode;
 pub mod graphics;
 pub mod raw;
+pub mod terminal;
 
-pub use self::{graphics::GraphicsMode, raw::RawMode};
+#[cfg(feature = "graphics")]
+pub use self::direct::DirectMode;
+pub use self::{graphics::GraphicsMode, raw::RawMode, terminal::TerminalMode};
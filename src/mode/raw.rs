@@ -3,23 +3,47 @@
 //! A display driver instance without high level functionality used as a return type from the
 //! builder. Used as a source to coerce the driver into richer modes like
 //! [`GraphicsMode`](../graphics/index.html).
+//!
+//! `RawMode` can also drive the display by itself, for applications managing their own buffer
+//! layout. Data is written in the display's native format: one byte per column of a page of 8
+//! rows, with the least significant bit at the top.
+//!
+//! ```rust
+//! use sh1106::{mode::RawMode, Builder};
+//! # let i2c = sh1106::test_helpers::I2cStub;
+//!
+//! // An 8x8 sprite stored page-ordered, e.g. in flash
+//! const SPRITE: [u8; 8] = [0x3C, 0x42, 0xA5, 0x81, 0xA5, 0x99, 0x42, 0x3C];
+//!
+//! let mut display: RawMode<_> = Builder::new().connect_i2c(i2c).into();
+//!
+//! display.init().unwrap();
+//!
+//! // Column 60 of page 4, i.e. rows 32 to 39
+//! display.draw_page(4, 60, &SPRITE).unwrap();
+//! ```
 
+use hal::{blocking::delay::DelayMs, digital::v2::OutputPin};
+
+#[cfg(feature = "async")]
+use crate::interface::AsyncDisplayInterface;
 use crate::{
-    interface::DisplayInterface, mode::displaymode::DisplayModeTrait, properties::DisplayProperties,
+    command::Command, displayrotation::DisplayRotation, interface::DisplayInterface,
+    mode::displaymode::DisplayModeTrait, properties::DisplayProperties, Error,
 };
 
+/// Number of columns in display RAM
+const RAM_COLUMNS: u8 = 132;
+
+/// Number of pages in display RAM
+const RAM_PAGES: u8 = 8;
+
 /// Raw display mode
-pub struct RawMode<DI>
-where
-    DI: DisplayInterface,
-{
+pub struct RawMode<DI> {
     properties: DisplayProperties<DI>,
 }
 
-impl<DI> DisplayModeTrait<DI> for RawMode<DI>
-where
-    DI: DisplayInterface,
-{
+impl<DI> DisplayModeTrait<DI> for RawMode<DI> {
     /// Create new RawMode instance
     fn new(properties: DisplayProperties<DI>) -> Self {
         RawMode { properties }
@@ -31,9 +55,366 @@ where
     }
 }
 
-impl<DI: DisplayInterface> RawMode<DI> {
+impl<DI> RawMode<DI> {
     /// Create a new raw display mode
     pub fn new(properties: DisplayProperties<DI>) -> Self {
         RawMode { properties }
     }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn get_dimensions(&self) -> (u8, u8) {
+        self.properties.get_dimensions()
+    }
+
+    /// Draw area start and end covering `len` columns of a page, starting at panel column
+    /// `column`, or `None` if that doesn't fit into the panel
+    fn page_area(&self, page: u8, column: u8, len: usize) -> Option<((u8, u8), (u8, u8))> {
+        let (display_width, _) = self.properties.get_size().dimensions();
+
+        if page >= RAM_PAGES || column as usize + len > display_width as usize {
+            return None;
+        }
+
+        let x = self.properties.get_column_offset() + column;
+
+        Some(((x, page * 8), (x + len as u8, page * 8 + 8)))
+    }
+
+    /// Whether a draw area from `start` to `end` covers at least one column and row, and lies
+    /// within display RAM
+    fn is_valid_area(start: (u8, u8), end: (u8, u8)) -> bool {
+        start.0 < end.0 && start.1 < end.1 && end.0 <= RAM_COLUMNS && end.1 <= RAM_PAGES * 8
+    }
+}
+
+impl<DI> RawMode<DI>
+where
+    DI: DisplayInterface,
+{
+    /// Initialise the display in column mode, see
+    /// [`DisplayProperties::init_column_mode`](crate::properties::DisplayProperties::init_column_mode).
+    /// Display RAM isn't cleared.
+    pub fn init(&mut self) -> Result<(), DI::Error> {
+        self.properties.init_column_mode()
+    }
+
+    /// Initialise the display following the power-on sequence from the datasheet, see
+    /// [`DisplayProperties::init_column_mode_with_delay`](crate::properties::DisplayProperties::init_column_mode_with_delay)
+    pub fn init_with_delay<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        self.properties.init_column_mode_with_delay(delay)
+    }
+
+    /// Write bytes to the draw area, one per column and page, continuing where the last call
+    /// stopped and wrapping to the next page at the end of the area
+    pub fn draw(&mut self, buffer: &[u8]) -> Result<(), DI::Error> {
+        self.properties.draw(buffer)
+    }
+
+    /// Set the display rotation
+    pub fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), DI::Error> {
+        self.properties.set_rotation(rot)
+    }
+
+    /// Mirror the display horizontally and/or vertically
+    pub fn set_flip(&mut self, flip_x: bool, flip_y: bool) -> Result<(), DI::Error> {
+        self.properties.set_flip(flip_x, flip_y)
+    }
+
+    /// Set the display contrast
+    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), DI::Error> {
+        self.properties.set_contrast(contrast)
+    }
+
+    /// Set the display RAM row shown on the first line of the panel, from 0-63
+    pub fn set_start_line(&mut self, line: u8) -> Result<(), DI::Error> {
+        self.properties.set_start_line(line)
+    }
+
+    /// Turn the display and charge pump off, see
+    /// [`DisplayProperties::sleep`](crate::properties::DisplayProperties::sleep)
+    pub fn sleep(&mut self) -> Result<(), DI::Error> {
+        self.properties.sleep()
+    }
+
+    /// Turn the charge pump and display back on, see
+    /// [`DisplayProperties::wake`](crate::properties::DisplayProperties::wake)
+    pub fn wake(&mut self) -> Result<(), DI::Error> {
+        self.properties.wake()
+    }
+
+    /// Turn the charge pump and display back on, waiting for the charge pump to settle
+    pub fn wake_with_delay<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        self.properties.wake_with_delay(delay)
+    }
+}
+
+impl<DI, CommE, PinE> RawMode<DI>
+where
+    DI: DisplayInterface<Error = Error<CommE, PinE>>,
+{
+    /// Send a single command to the display, see
+    /// [`DisplayProperties::send_command`](crate::properties::DisplayProperties::send_command).
+    pub fn send_command(&mut self, command: Command) -> Result<(), DI::Error> {
+        self.properties.send_command(command)
+    }
+
+    /// Reset the display by pulsing its reset pin, see
+    /// [`DisplayProperties::reset`](crate::properties::DisplayProperties::reset)
+    pub fn reset<RST, DELAY>(
+        &mut self,
+        rst: &mut RST,
+        delay: &mut DELAY,
+    ) -> Result<(), Error<CommE, RST::Error>>
+    where
+        RST: OutputPin,
+        DELAY: DelayMs<u8>,
+    {
+        self.properties.reset(rst, delay)
+    }
+
+    /// Set the area of display RAM written by [`draw`](Self::draw), from `start` to `end`
+    /// (exclusive) in display RAM columns and rows. Rows are rounded to pages of 8 rows, and the
+    /// column offset of the panel isn't applied.
+    ///
+    /// Returns [`Error::InvalidArgument`] if the area is empty or extends past the 132 columns and
+    /// 64 rows of display RAM.
+    pub fn set_draw_area(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), DI::Error> {
+        if !Self::is_valid_area(start, end) {
+            return Err(Error::InvalidArgument);
+        }
+
+        self.properties.set_draw_area(start, end)
+    }
+
+    /// Write bytes to a page of display RAM, from 0-7, starting at `column` of the panel. The
+    /// column offset of the panel is applied.
+    ///
+    /// Returns [`Error::InvalidArgument`] if the page doesn't exist or the data doesn't fit into
+    /// the width of the panel.
+    pub fn draw_page(&mut self, page: u8, column: u8, data: &[u8]) -> Result<(), DI::Error> {
+        let (start, end) = self
+            .page_area(page, column, data.len())
+            .ok_or(Error::InvalidArgument)?;
+
+        self.properties.set_draw_area(start, end)?;
+        self.properties.draw(data)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<DI, CommE, PinE> RawMode<DI>
+where
+    DI: AsyncDisplayInterface<Error = Error<CommE, PinE>>,
+{
+    /// Asynchronous version of [`send_command`](Self::send_command)
+    pub async fn send_command_async(&mut self, command: Command) -> Result<(), DI::Error> {
+        self.properties.send_command_async(command).await
+    }
+
+    /// Asynchronous version of [`set_draw_area`](Self::set_draw_area)
+    pub async fn set_draw_area_async(
+        &mut self,
+        start: (u8, u8),
+        end: (u8, u8),
+    ) -> Result<(), DI::Error> {
+        if !Self::is_valid_area(start, end) {
+            return Err(Error::InvalidArgument);
+        }
+
+        self.properties.set_draw_area_async(start, end).await
+    }
+
+    /// Asynchronous version of [`draw_page`](Self::draw_page)
+    pub async fn draw_page_async(
+        &mut self,
+        page: u8,
+        column: u8,
+        data: &[u8],
+    ) -> Result<(), DI::Error> {
+        let (start, end) = self
+            .page_area(page, column, data.len())
+            .ok_or(Error::InvalidArgument)?;
+
+        self.properties.set_draw_area_async(start, end).await?;
+        self.properties.draw_async(data).await
+    }
+}
+
+#[cfg(feature = "async")]
+impl<DI> RawMode<DI>
+where
+    DI: AsyncDisplayInterface,
+{
+    /// Asynchronous version of [`init`](Self::init)
+    pub async fn init_async(&mut self) -> Result<(), DI::Error> {
+        self.properties.init_column_mode_async().await
+    }
+
+    /// Asynchronous version of [`init_with_delay`](Self::init_with_delay)
+    pub async fn init_with_delay_async<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: embedded_hal_async::delay::DelayNs,
+    {
+        self.properties
+            .init_column_mode_with_delay_async(delay)
+            .await
+    }
+
+    /// Asynchronous version of [`draw`](Self::draw)
+    pub async fn draw_async(&mut self, buffer: &[u8]) -> Result<(), DI::Error> {
+        self.properties.draw_async(buffer).await
+    }
+
+    /// Asynchronous version of [`set_rotation`](Self::set_rotation)
+    pub async fn set_rotation_async(&mut self, rot: DisplayRotation) -> Result<(), DI::Error> {
+        self.properties.set_rotation_async(rot).await
+    }
+
+    /// Asynchronous version of [`set_flip`](Self::set_flip)
+    pub async fn set_flip_async(&mut self, flip_x: bool, flip_y: bool) -> Result<(), DI::Error> {
+        self.properties.set_flip_async(flip_x, flip_y).await
+    }
+
+    /// Asynchronous version of [`set_contrast`](Self::set_contrast)
+    pub async fn set_contrast_async(&mut self, contrast: u8) -> Result<(), DI::Error> {
+        self.properties.set_contrast_async(contrast).await
+    }
+
+    /// Asynchronous version of [`set_start_line`](Self::set_start_line)
+    pub async fn set_start_line_async(&mut self, line: u8) -> Result<(), DI::Error> {
+        self.properties.set_start_line_async(line).await
+    }
+
+    /// Asynchronous version of [`sleep`](Self::sleep)
+    pub async fn sleep_async(&mut self) -> Result<(), DI::Error> {
+        self.properties.sleep_async().await
+    }
+
+    /// Asynchronous version of [`wake`](Self::wake)
+    pub async fn wake_async(&mut self) -> Result<(), DI::Error> {
+        self.properties.wake_async().await
+    }
+
+    /// Asynchronous version of [`wake_with_delay`](Self::wake_with_delay)
+    pub async fn wake_with_delay_async<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: embedded_hal_async::delay::DelayNs,
+    {
+        self.properties.wake_with_delay_async(delay).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::Builder,
+        test_helpers::{Recorder, Transaction},
+    };
+
+    #[test]
+    fn draw_page_applies_column_offset() {
+        let recorder = Recorder::new();
+        let mut display: RawMode<_> = Builder::new().connect_i2c(recorder.i2c()).into();
+
+        display.draw_page(4, 60, &[0x3C, 0x42]).unwrap();
+
+        let log = recorder.log();
+        let writes = log.transactions().map(|t| match t {
+            Transaction::I2cWrite { bytes, .. } => bytes,
+            _ => panic!("unexpected transaction {:?}", t),
+        });
+
+        // Page 4, column 60 + 2
+        assert!(writes.take(4).eq([
+            &[0x00, 0xB4][..],
+            &[0x00, 0x0E],
+            &[0x00, 0x13],
+            &[0x40, 0x3C, 0x42],
+        ]));
+    }
+
+    #[test]
+    fn draw_page_rejects_data_outside_of_panel() {
+        let recorder = Recorder::new();
+        let mut display: RawMode<_> = Builder::new().connect_i2c(recorder.i2c()).into();
+
+        assert!(matches!(
+            display.draw_page(8, 0, &[0xFF]),
+            Err(Error::InvalidArgument)
+        ));
+        assert!(matches!(
+            display.draw_page(0, 127, &[0xFF, 0xFF]),
+            Err(Error::InvalidArgument)
+        ));
+        assert!(recorder.log().is_empty());
+        assert!(display.draw_page(7, 127, &[0xFF]).is_ok());
+    }
+
+    #[test]
+    fn set_draw_area_rejects_invalid_areas() {
+        let recorder = Recorder::new();
+        let mut display: RawMode<_> = Builder::new().connect_i2c(recorder.i2c()).into();
+
+        for (start, end) in [
+            // Empty
+            ((10, 0), (10, 8)),
+            ((10, 8), (20, 8)),
+            // Start after end
+            ((20, 0), (10, 8)),
+            ((0, 16), (10, 8)),
+            // Past display RAM
+            ((0, 0), (133, 8)),
+            ((0, 56), (10, 72)),
+            ((0, 64), (10, 72)),
+        ] {
+            assert!(
+                matches!(
+                    display.set_draw_area(start, end),
+                    Err(Error::InvalidArgument)
+                ),
+                "{:?} {:?}",
+                start,
+                end
+            );
+        }
+        assert!(recorder.log().is_empty());
+
+        display.set_draw_area((0, 0), (132, 64)).unwrap();
+        display.set_draw_area((131, 63), (132, 64)).unwrap();
+        display.draw(&[0xFF, 0xFF]).unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn delayed_power_up_async_matches_blocking() {
+        use crate::test_helpers::block_on;
+
+        let blocking = Recorder::new();
+        let mut display: RawMode<_> = Builder::new().connect_i2c(blocking.i2c()).into();
+        display.init_with_delay(&mut blocking.delay()).unwrap();
+        display.sleep().unwrap();
+        display.wake_with_delay(&mut blocking.delay()).unwrap();
+
+        let asynchronous = Recorder::new();
+        let mut display: RawMode<_> = Builder::new().connect_i2c_async(asynchronous.i2c()).into();
+        block_on(display.init_with_delay_async(&mut asynchronous.delay())).unwrap();
+        block_on(display.sleep_async()).unwrap();
+        block_on(display.wake_with_delay_async(&mut asynchronous.delay())).unwrap();
+
+        assert!(blocking
+            .log()
+            .transactions()
+            .any(|t| matches!(t, Transaction::Delay { .. })));
+        assert!(blocking
+            .log()
+            .transactions()
+            .eq(asynchronous.log().transactions()));
+    }
 }
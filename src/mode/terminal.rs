@@ -0,0 +1,644 @@
+//! Unbuffered terminal mode
+//!
+//! Renders characters from a built-in 8x8 pixel font straight into the display RAM, without a
+//! framebuffer. The cursor wraps at the end of each line, and once the bottom of the display is
+//! reached the display start line is moved so that older lines scroll off the top.
+//!
+//! [`TerminalMode`] implements [`core::fmt::Write`], so it can be used with `write!`:
+//!
+//! ```rust,no_run
+//! use core::fmt::Write;
+//! use sh1106::{mode::TerminalMode, Builder};
+//! # let i2c = sh1106::test_helpers::I2cStub;
+//!
+//! let mut display: TerminalMode<_> = Builder::new().connect_i2c(i2c).into();
+//!
+//! display.init().unwrap();
+//!
+//! writeln!(display, "Hello world!").unwrap();
+//! write!(display, "Uptime: {}s", 42).unwrap();
+//! ```
+//!
+//! Only the hardware orientations, [`DisplayRotation::Rotate0`] and
+//! [`DisplayRotation::Rotate180`], are supported in this mode. `init` returns
+//! [`Error::InvalidArgument`] for any other rotation set in the builder.
+
+use core::fmt;
+
+use hal::{blocking::delay::DelayMs, digital::v2::OutputPin};
+
+#[cfg(feature = "async")]
+use crate::interface::AsyncDisplayInterface;
+use crate::{
+    command::Command, displayrotation::DisplayRotation, interface::DisplayInterface,
+    mode::displaymode::DisplayModeTrait, properties::DisplayProperties, Error,
+};
+
+/// Width and height of a character cell in pixels
+const CHAR_SIZE: u8 = 8;
+
+/// Number of pages in display RAM, independent of the panel height
+const RAM_PAGES: u8 = 8;
+
+/// Terminal mode handler
+pub struct TerminalMode<DI> {
+    properties: DisplayProperties<DI>,
+    column: u8,
+    row: u8,
+    /// Display RAM page shown on the top line of the panel
+    top_page: u8,
+}
+
+impl<DI> DisplayModeTrait<DI> for TerminalMode<DI> {
+    /// Create new TerminalMode instance
+    fn new(properties: DisplayProperties<DI>) -> Self {
+        TerminalMode {
+            properties,
+            column: 0,
+            row: 0,
+            top_page: 0,
+        }
+    }
+
+    /// Release all resources used by TerminalMode
+    fn release(self) -> DisplayProperties<DI> {
+        self.properties
+    }
+}
+
+impl<DI> TerminalMode<DI> {
+    /// Get the size of the terminal in characters as `(columns, rows)`. Panels narrower than a
+    /// character have a single column, clipped to the width of the panel.
+    pub fn get_dimensions(&self) -> (u8, u8) {
+        let (display_width, display_height) = self.properties.get_size().dimensions();
+
+        (
+            (display_width / CHAR_SIZE).max(1),
+            display_height / CHAR_SIZE,
+        )
+    }
+
+    /// Get the cursor position as `(column, row)`
+    pub fn get_position(&self) -> (u8, u8) {
+        (self.column, self.row)
+    }
+
+    /// Move the cursor. The position is clamped to the terminal dimensions.
+    pub fn set_position(&mut self, column: u8, row: u8) {
+        let (columns, rows) = self.get_dimensions();
+
+        self.column = column.min(columns - 1);
+        self.row = row.min(rows - 1);
+    }
+
+    /// Display RAM page holding the given terminal row
+    fn ram_page(&self, row: u8) -> u8 {
+        (self.top_page + row) % RAM_PAGES
+    }
+
+    /// Draw area start and end covering `width` pixels of a RAM page, starting at terminal column
+    /// `column`
+    fn cell_area(&self, column: u8, page: u8, width: u8) -> ((u8, u8), (u8, u8)) {
+        let column_offset = self.properties.get_column_offset();
+        let x = column_offset + column * CHAR_SIZE;
+        let y = page * CHAR_SIZE;
+
+        ((x, y), (x + width, y + CHAR_SIZE))
+    }
+}
+
+impl<DI> TerminalMode<DI>
+where
+    DI: DisplayInterface,
+{
+    /// Turn the display and charge pump off. Text printed while asleep is shown on waking.
+    pub fn sleep(&mut self) -> Result<(), DI::Error> {
+        self.properties.sleep()
+    }
+
+    /// Turn the charge pump and display back on, see
+    /// [`DisplayProperties::wake`](crate::properties::DisplayProperties::wake)
+    pub fn wake(&mut self) -> Result<(), DI::Error> {
+        self.properties.wake()
+    }
+
+    /// Turn the charge pump and display back on, waiting for the charge pump to settle
+    pub fn wake_with_delay<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        self.properties.wake_with_delay(delay)
+    }
+
+    /// Clear the whole display RAM and move the cursor to the top left corner
+    pub fn clear(&mut self) -> Result<(), DI::Error> {
+        for page in 0..RAM_PAGES {
+            self.clear_page(page)?;
+        }
+
+        self.column = 0;
+        self.row = 0;
+        self.top_page = 0;
+
+        self.properties.set_start_line(0)
+    }
+
+    /// Print a character at the cursor position and advance the cursor. `\n` moves the cursor to
+    /// the start of the next line and `\r` to the start of the current line.
+    pub fn print_char(&mut self, c: char) -> Result<(), DI::Error> {
+        let (columns, _) = self.get_dimensions();
+
+        match c {
+            '\n' => self.new_line(),
+            '\r' => {
+                self.column = 0;
+
+                Ok(())
+            }
+            c => {
+                // Wrap lazily so that a full line followed by a newline doesn't leave an empty line
+                if self.column >= columns {
+                    self.new_line()?;
+                }
+
+                let (display_width, _) = self.properties.get_size().dimensions();
+                let width = CHAR_SIZE.min(display_width - self.column * CHAR_SIZE);
+                let (start, end) = self.cell_area(self.column, self.ram_page(self.row), width);
+
+                self.properties.set_draw_area(start, end)?;
+                self.properties.draw(&glyph(c)[..width as usize])?;
+
+                self.column += 1;
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Print a string at the cursor position
+    pub fn print_str(&mut self, s: &str) -> Result<(), DI::Error> {
+        for c in s.chars() {
+            self.print_char(c)?;
+        }
+
+        Ok(())
+    }
+
+    /// Mirror the display horizontally and/or vertically
+    pub fn set_flip(&mut self, flip_x: bool, flip_y: bool) -> Result<(), DI::Error> {
+        self.properties.set_flip(flip_x, flip_y)
+    }
+
+    /// Set the display contrast
+    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), DI::Error> {
+        self.properties.set_contrast(contrast)
+    }
+
+    /// Move the cursor to the start of the next line, scrolling the display up by one line if the
+    /// cursor is on the last line
+    fn new_line(&mut self) -> Result<(), DI::Error> {
+        let (_, rows) = self.get_dimensions();
+
+        self.column = 0;
+
+        if self.row + 1 < rows {
+            self.row += 1;
+
+            return Ok(());
+        }
+
+        self.top_page = (self.top_page + 1) % RAM_PAGES;
+        self.clear_page(self.ram_page(rows - 1))?;
+
+        self.properties.set_start_line(self.top_page * CHAR_SIZE)
+    }
+
+    fn clear_page(&mut self, page: u8) -> Result<(), DI::Error> {
+        let (display_width, _) = self.properties.get_size().dimensions();
+        let (start, end) = self.cell_area(0, page, display_width);
+
+        self.properties.set_draw_area(start, end)?;
+        self.properties.draw(&[0; 132][..display_width as usize])
+    }
+}
+
+impl<DI, CommE, PinE> TerminalMode<DI>
+where
+    DI: DisplayInterface<Error = Error<CommE, PinE>>,
+{
+    /// Send a single command to the display, see
+    /// [`DisplayProperties::send_command`](crate::properties::DisplayProperties::send_command).
+    ///
+    /// Commands that move the start line or address pointer will confuse the cursor.
+    pub fn send_command(&mut self, command: Command) -> Result<(), DI::Error> {
+        self.properties.send_command(command)
+    }
+
+    /// Reset the display by pulsing its reset pin, see
+    /// [`DisplayProperties::reset`](crate::properties::DisplayProperties::reset)
+    pub fn reset<RST, DELAY>(
+        &mut self,
+        rst: &mut RST,
+        delay: &mut DELAY,
+    ) -> Result<(), Error<CommE, RST::Error>>
+    where
+        RST: OutputPin,
+        DELAY: DelayMs<u8>,
+    {
+        self.properties.reset(rst, delay)
+    }
+
+    /// Initialise the display and clear the terminal
+    ///
+    /// Returns [`Error::InvalidArgument`] if the display is rotated by 90 or 270 degrees, e.g.
+    /// with [`Builder::with_rotation`](crate::Builder::with_rotation).
+    pub fn init(&mut self) -> Result<(), DI::Error> {
+        check_rotation(self.properties.get_rotation())?;
+
+        self.properties.init_column_mode()?;
+
+        self.clear()
+    }
+
+    /// Initialise the display following the power-on sequence from the datasheet and clear the
+    /// terminal, see
+    /// [`DisplayProperties::init_column_mode_with_delay`](crate::properties::DisplayProperties::init_column_mode_with_delay)
+    ///
+    /// Returns [`Error::InvalidArgument`] if the display is rotated by 90 or 270 degrees.
+    pub fn init_with_delay<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        check_rotation(self.properties.get_rotation())?;
+
+        self.properties.init_column_mode_with_delay(delay)?;
+
+        self.clear()
+    }
+
+    /// Set the display rotation
+    ///
+    /// Returns [`Error::InvalidArgument`] for [`DisplayRotation::Rotate90`] and
+    /// [`DisplayRotation::Rotate270`], the terminal can only be drawn in the hardware
+    /// orientations.
+    pub fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), DI::Error> {
+        check_rotation(rot)?;
+
+        self.properties.set_rotation(rot)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<DI, CommE, PinE> TerminalMode<DI>
+where
+    DI: AsyncDisplayInterface<Error = Error<CommE, PinE>>,
+{
+    /// Asynchronous version of [`send_command`](Self::send_command)
+    pub async fn send_command_async(&mut self, command: Command) -> Result<(), DI::Error> {
+        self.properties.send_command_async(command).await
+    }
+}
+
+impl<DI> fmt::Write for TerminalMode<DI>
+where
+    DI: DisplayInterface,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.print_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+/// Check that the terminal can be drawn with the rotation, i.e. that it's a hardware orientation
+fn check_rotation<CommE, PinE>(rot: DisplayRotation) -> Result<(), Error<CommE, PinE>> {
+    match rot {
+        DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => Ok(()),
+        DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => Err(Error::InvalidArgument),
+    }
+}
+
+/// Column-major bitmap of a character, with the least significant bit at the top. Characters
+/// without a glyph are rendered blank.
+fn glyph(c: char) -> [u8; 8] {
+    const FONT: [[u8; 6]; 95] = [
+        // !
+        [0x00, 0x2f, 0x00, 0x00, 0x00, 0x00],
+        // "
+        [0x03, 0x00, 0x03, 0x00, 0x00, 0x00],
+        // #
+        [0x12, 0x3f, 0x12, 0x12, 0x3f, 0x12],
+        // $
+        [0x2e, 0x2a, 0x7f, 0x2a, 0x3a, 0x00],
+        // %
+        [0x23, 0x13, 0x08, 0x04, 0x32, 0x31],
+        // &
+        [0x10, 0x2a, 0x25, 0x2a, 0x10, 0x20],
+        // '
+        [0x02, 0x01, 0x00, 0x00, 0x00, 0x00],
+        // (
+        [0x1e, 0x21, 0x00, 0x00, 0x00, 0x00],
+        // )
+        [0x21, 0x1e, 0x00, 0x00, 0x00, 0x00],
+        // *
+        [0x08, 0x2a, 0x1c, 0x2a, 0x08, 0x00],
+        // +
+        [0x08, 0x08, 0x3e, 0x08, 0x08, 0x00],
+        // ,
+        [0x80, 0x60, 0x00, 0x00, 0x00, 0x00],
+        // -
+        [0x08, 0x08, 0x08, 0x08, 0x08, 0x00],
+        // .
+        [0x30, 0x30, 0x00, 0x00, 0x00, 0x00],
+        // /
+        [0x20, 0x10, 0x08, 0x04, 0x02, 0x00],
+        // 0
+        [0x1e, 0x31, 0x29, 0x25, 0x23, 0x1e],
+        // 1
+        [0x22, 0x21, 0x3f, 0x20, 0x20, 0x20],
+        // 2
+        [0x32, 0x29, 0x29, 0x29, 0x29, 0x26],
+        // 3
+        [0x12, 0x21, 0x21, 0x25, 0x25, 0x1a],
+        // 4
+        [0x18, 0x14, 0x12, 0x3f, 0x10, 0x00],
+        // 5
+        [0x17, 0x25, 0x25, 0x25, 0x25, 0x19],
+        // 6
+        [0x1e, 0x25, 0x25, 0x25, 0x25, 0x18],
+        // 7
+        [0x01, 0x01, 0x31, 0x09, 0x05, 0x03],
+        // 8
+        [0x1a, 0x25, 0x25, 0x25, 0x25, 0x1a],
+        // 9
+        [0x06, 0x29, 0x29, 0x29, 0x29, 0x1e],
+        // :
+        [0x24, 0x00, 0x00, 0x00, 0x00, 0x00],
+        // ;
+        [0x80, 0x64, 0x00, 0x00, 0x00, 0x00],
+        // <
+        [0x08, 0x14, 0x22, 0x00, 0x00, 0x00],
+        // =
+        [0x14, 0x14, 0x14, 0x14, 0x14, 0x00],
+        // >
+        [0x22, 0x14, 0x08, 0x00, 0x00, 0x00],
+        // ?
+        [0x02, 0x01, 0x01, 0x29, 0x05, 0x02],
+        // @
+        [0x1e, 0x21, 0x2d, 0x2b, 0x2d, 0x0e],
+        // A
+        [0x3e, 0x09, 0x09, 0x09, 0x09, 0x3e],
+        // B
+        [0x3f, 0x25, 0x25, 0x25, 0x25, 0x1a],
+        // C
+        [0x1e, 0x21, 0x21, 0x21, 0x21, 0x12],
+        // D
+        [0x3f, 0x21, 0x21, 0x21, 0x12, 0x0c],
+        // E
+        [0x3f, 0x25, 0x25, 0x25, 0x25, 0x21],
+        // F
+        [0x3f, 0x05, 0x05, 0x05, 0x05, 0x01],
+        // G
+        [0x1e, 0x21, 0x21, 0x21, 0x29, 0x1a],
+        // H
+        [0x3f, 0x04, 0x04, 0x04, 0x04, 0x3f],
+        // I
+        [0x21, 0x21, 0x3f, 0x21, 0x21, 0x00],
+        // J
+        [0x10, 0x20, 0x20, 0x20, 0x20, 0x1f],
+        // K
+        [0x3f, 0x04, 0x0c, 0x0a, 0x11, 0x20],
+        // L
+        [0x3f, 0x20, 0x20, 0x20, 0x20, 0x20],
+        // M
+        [0x3f, 0x02, 0x04, 0x04, 0x02, 0x3f],
+        // N
+        [0x3f, 0x02, 0x04, 0x08, 0x10, 0x3f],
+        // O
+        [0x1e, 0x21, 0x21, 0x21, 0x21, 0x1e],
+        // P
+        [0x3f, 0x09, 0x09, 0x09, 0x09, 0x06],
+        // Q
+        [0x1e, 0x21, 0x29, 0x31, 0x21, 0x5e],
+        // R
+        [0x3f, 0x09, 0x09, 0x09, 0x19, 0x26],
+        // S
+        [0x12, 0x25, 0x25, 0x25, 0x25, 0x18],
+        // T
+        [0x01, 0x01, 0x01, 0x3f, 0x01, 0x01],
+        // U
+        [0x1f, 0x20, 0x20, 0x20, 0x20, 0x1f],
+        // V
+        [0x0f, 0x10, 0x20, 0x20, 0x10, 0x0f],
+        // W
+        [0x1f, 0x20, 0x10, 0x10, 0x20, 0x1f],
+        // X
+        [0x21, 0x12, 0x0c, 0x0c, 0x12, 0x21],
+        // Y
+        [0x01, 0x02, 0x3c, 0x02, 0x01, 0x00],
+        // Z
+        [0x21, 0x31, 0x29, 0x25, 0x23, 0x21],
+        // [
+        [0x3f, 0x21, 0x00, 0x00, 0x00, 0x00],
+        // \
+        [0x02, 0x04, 0x08, 0x10, 0x20, 0x00],
+        // ]
+        [0x21, 0x3f, 0x00, 0x00, 0x00, 0x00],
+        // ^
+        [0x04, 0x02, 0x3f, 0x02, 0x04, 0x00],
+        // _
+        [0x40, 0x40, 0x40, 0x40, 0x40, 0x40],
+        // `
+        [0x01, 0x02, 0x00, 0x00, 0x00, 0x00],
+        // a
+        [0x10, 0x2a, 0x2a, 0x2a, 0x3c, 0x00],
+        // b
+        [0x3f, 0x24, 0x24, 0x24, 0x18, 0x00],
+        // c
+        [0x1c, 0x22, 0x22, 0x22, 0x00, 0x00],
+        // d
+        [0x18, 0x24, 0x24, 0x24, 0x3f, 0x00],
+        // e
+        [0x1c, 0x2a, 0x2a, 0x2a, 0x24, 0x00],
+        // f
+        [0x00, 0x3e, 0x05, 0x01, 0x00, 0x00],
+        // g
+        [0x18, 0xa4, 0xa4, 0xa4, 0x7c, 0x00],
+        // h
+        [0x3f, 0x04, 0x04, 0x04, 0x38, 0x00],
+        // i
+        [0x00, 0x24, 0x3d, 0x20, 0x00, 0x00],
+        // j
+        [0x20, 0x40, 0x40, 0x3d, 0x00, 0x00],
+        // k
+        [0x3f, 0x0c, 0x12, 0x20, 0x00, 0x00],
+        // l
+        [0x1f, 0x20, 0x20, 0x00, 0x00, 0x00],
+        // m
+        [0x3e, 0x02, 0x3c, 0x02, 0x3c, 0x00],
+        // n
+        [0x3e, 0x02, 0x02, 0x02, 0x3c, 0x00],
+        // o
+        [0x1c, 0x22, 0x22, 0x22, 0x1c, 0x00],
+        // p
+        [0xfc, 0x24, 0x24, 0x24, 0x18, 0x00],
+        // q
+        [0x18, 0x24, 0x24, 0x24, 0xfc, 0x00],
+        // r
+        [0x3e, 0x04, 0x02, 0x02, 0x00, 0x00],
+        // s
+        [0x24, 0x2a, 0x2a, 0x2a, 0x10, 0x00],
+        // t
+        [0x02, 0x1f, 0x22, 0x20, 0x00, 0x00],
+        // u
+        [0x1e, 0x20, 0x20, 0x20, 0x1e, 0x00],
+        // v
+        [0x06, 0x18, 0x20, 0x18, 0x06, 0x00],
+        // w
+        [0x1e, 0x30, 0x1c, 0x30, 0x1e, 0x00],
+        // x
+        [0x22, 0x14, 0x08, 0x14, 0x22, 0x00],
+        // y
+        [0x1c, 0xa0, 0xa0, 0xa0, 0x7c, 0x00],
+        // z
+        [0x22, 0x32, 0x2a, 0x26, 0x22, 0x00],
+        // {
+        [0x0c, 0x3f, 0x21, 0x00, 0x00, 0x00],
+        // |
+        [0x3f, 0x00, 0x00, 0x00, 0x00, 0x00],
+        // }
+        [0x21, 0x3f, 0x0c, 0x00, 0x00, 0x00],
+        // ~
+        [0x02, 0x01, 0x02, 0x01, 0x00, 0x00],
+        // blank
+        [0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    ];
+
+    let g = (c as usize)
+        .checked_sub(b'!'.into())
+        .and_then(|idx| FONT.get(idx))
+        .unwrap_or(&FONT[FONT.len() - 1]);
+
+    [0, g[0], g[1], g[2], g[3], g[4], g[5], 0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::Builder,
+        displaysize::{CustomSize, DisplaySize},
+        test_helpers::{I2cStub, Recorder, Transaction},
+    };
+    use core::fmt::Write;
+
+    #[test]
+    fn cursor_wraps_lazily() {
+        let mut display: TerminalMode<_> = Builder::new().connect_i2c(I2cStub).into();
+
+        display.print_str("0123456789abcdef").unwrap();
+        assert_eq!(display.get_position(), (16, 0));
+
+        display.print_char('\n').unwrap();
+        assert_eq!(display.get_position(), (0, 1));
+
+        display.print_str("0123456789abcdefg").unwrap();
+        assert_eq!(display.get_position(), (1, 2));
+    }
+
+    #[test]
+    fn scrolls_at_bottom() {
+        let mut display: TerminalMode<_> = Builder::new()
+            .with_size(DisplaySize::Display128x32)
+            .connect_i2c(I2cStub)
+            .into();
+
+        assert_eq!(display.get_dimensions(), (16, 4));
+
+        write!(display, "1\n2\n3\n4").unwrap();
+        assert_eq!(display.get_position(), (1, 3));
+        assert_eq!(display.top_page, 0);
+
+        writeln!(display).unwrap();
+        assert_eq!(display.get_position(), (0, 3));
+        assert_eq!(display.top_page, 1);
+        assert_eq!(display.ram_page(3), 4);
+
+        for _ in 0..7 {
+            writeln!(display).unwrap();
+        }
+        assert_eq!(display.top_page, 0);
+    }
+
+    #[test]
+    fn narrow_panel_has_a_clipped_column() {
+        let recorder = Recorder::new();
+        let size = CustomSize::new(4, 16).with_column_offset(128);
+        let mut display: TerminalMode<_> = Builder::new()
+            .with_size(DisplaySize::Custom(size))
+            .connect_i2c(recorder.i2c())
+            .into();
+
+        assert_eq!(display.get_dimensions(), (1, 2));
+
+        display.set_position(5, 5);
+        assert_eq!(display.get_position(), (0, 1));
+
+        display.print_char('!').unwrap();
+        let log = recorder.log();
+        let data = log
+            .transactions()
+            .filter_map(|transaction| match transaction {
+                Transaction::I2cWrite { bytes, .. } if bytes[0] == 0x40 => Some(bytes),
+                _ => None,
+            });
+        assert_eq!(data.last(), Some(&[0x40, 0, 0, 0x2f, 0][..]));
+        drop(log);
+
+        display.print_str("ab").unwrap();
+        assert_eq!(display.get_position(), (1, 1));
+    }
+
+    #[test]
+    fn rotation_must_be_hardware_orientation() {
+        let mut display: TerminalMode<_> = Builder::new().connect_i2c(I2cStub).into();
+
+        display.set_rotation(DisplayRotation::Rotate180).unwrap();
+        assert!(matches!(
+            display.set_rotation(DisplayRotation::Rotate90),
+            Err(Error::InvalidArgument)
+        ));
+        assert!(matches!(
+            display.set_rotation(DisplayRotation::Rotate270),
+            Err(Error::InvalidArgument)
+        ));
+    }
+
+    #[test]
+    fn init_rejects_rotation_from_builder() {
+        let recorder = Recorder::new();
+        let mut display: TerminalMode<_> = Builder::new()
+            .with_rotation(DisplayRotation::Rotate90)
+            .connect_i2c(recorder.i2c())
+            .into();
+
+        assert!(matches!(display.init(), Err(Error::InvalidArgument)));
+        assert!(matches!(
+            display.init_with_delay(&mut recorder.delay()),
+            Err(Error::InvalidArgument)
+        ));
+        assert!(recorder.log().is_empty());
+
+        let mut display: TerminalMode<_> = Builder::new()
+            .with_rotation(DisplayRotation::Rotate180)
+            .connect_i2c(I2cStub)
+            .into();
+
+        display.init().unwrap();
+    }
+
+    #[test]
+    fn unknown_characters_are_blank() {
+        assert_eq!(glyph(' '), [0; 8]);
+        assert_eq!(glyph('ä'), [0; 8]);
+        assert_eq!(glyph('!'), [0, 0, 0x2f, 0, 0, 0, 0, 0]);
+    }
+}
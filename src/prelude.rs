@@ -3,6 +3,11 @@
 pub use super::{
     displayrotation::DisplayRotation,
     displaysize::DisplaySize,
-    interface::{I2cInterface, SpiInterface},
-    mode::GraphicsMode,
+    interface::{
+        DataCommandInterface, I2cDeviceInterface, I2cInterface, SpiDeviceInterface, SpiInterface,
+    },
+    mode::{displaymode::DisplayModeTrait, GraphicsMode, RawMode, TerminalMode},
 };
+
+#[cfg(feature = "graphics")]
+pub use super::mode::DirectMode;
@@ -1,27 +1,41 @@
 //! Container to store and set display properties
 
+use hal::{blocking::delay::DelayMs, digital::v2::OutputPin};
+
+#[cfg(feature = "async")]
+use crate::interface::AsyncDisplayInterface;
 use crate::{
-    command::{Command, VcomhLevel},
+    command::Command,
     displayrotation::DisplayRotation,
-    displaysize::DisplaySize,
-    interface::DisplayInterface,
+    displaysize::{DisplaySize, PanelSize},
+    initconfig::InitConfig,
+    interface::{reset, DisplayInterface},
+    Error,
 };
 
+/// Time for the output of the DC-DC converter to settle after turning it on, in milliseconds
+const CHARGE_PUMP_SETTLE_MS: u8 = 100;
+
+/// Number of columns in display RAM
+const RAM_COLUMNS: u8 = 132;
+
 /// Display properties struct
 pub struct DisplayProperties<DI> {
     iface: DI,
     display_size: DisplaySize,
+    /// Whether the size was set with `Builder::with_size` rather than left at its default
+    size_configured: bool,
     display_rotation: DisplayRotation,
+    flip_x: bool,
+    flip_y: bool,
+    init_config: InitConfig,
     draw_area_start: (u8, u8),
     draw_area_end: (u8, u8),
     draw_column: u8,
     draw_row: u8,
 }
 
-impl<DI> DisplayProperties<DI>
-where
-    DI: DisplayInterface,
-{
+impl<DI> DisplayProperties<DI> {
     /// Create new DisplayProperties instance
     pub fn new(
         iface: DI,
@@ -31,7 +45,11 @@ where
         DisplayProperties {
             iface,
             display_size,
+            size_configured: false,
             display_rotation,
+            flip_x: false,
+            flip_y: false,
+            init_config: InitConfig::new(),
             draw_area_start: (0, 0),
             draw_area_end: (0, 0),
             draw_column: 0,
@@ -39,50 +57,255 @@ where
         }
     }
 
+    /// Release the display interface
+    pub fn release(self) -> DI {
+        self.iface
+    }
+
+    /// Get the configured display size
+    pub fn get_size(&self) -> DisplaySize {
+        self.display_size
+    }
+
+    /// Mark the display size as chosen by the user, so modes sized for a different panel refuse it
+    pub(crate) fn set_size_configured(&mut self, configured: bool) {
+        self.size_configured = configured;
+    }
+
+    /// Switch to the display size of a mode whose buffer is sized for a particular panel
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size was set with `Builder::with_size` and differs from the panel size.
+    pub(crate) fn apply_panel_size<SIZE: PanelSize>(&mut self) {
+        let display_size = SIZE::display_size(self.display_size);
+
+        assert!(
+            !self.size_configured || display_size == self.display_size,
+            "panel size of the mode doesn't match the size set with Builder::with_size"
+        );
+
+        self.display_size = display_size;
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the display
+    pub fn get_dimensions(&self) -> (u8, u8) {
+        let (w, h) = self.display_size.dimensions();
+
+        match self.display_rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (w, h),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (h, w),
+        }
+    }
+
+    /// Get the first display RAM column sent to the panel, taking into account the current
+    /// rotation and mirroring of the display
+    ///
+    /// Turning segment remapping off, as `Rotate90` and `Rotate180` do, reverses the order of
+    /// display RAM columns on the panel. The panel then starts as many columns from the end of the
+    /// 132 columns of display RAM as it did from the start.
+    pub fn get_column_offset(&self) -> u8 {
+        let (width, _) = self.display_size.dimensions();
+        let column_offset = self.display_size.column_offset();
+        let [segment_remap, _] = self.orientation_commands();
+
+        if segment_remap == Command::SegmentRemap(true) {
+            column_offset
+        } else {
+            RAM_COLUMNS - width - column_offset
+        }
+    }
+
+    /// Get the display rotation
+    pub fn get_rotation(&self) -> DisplayRotation {
+        self.display_rotation
+    }
+
+    /// Get whether the display is mirrored horizontally and vertically, as `(flip_x, flip_y)`
+    pub fn get_flip(&self) -> (bool, bool) {
+        (self.flip_x, self.flip_y)
+    }
+
+    /// Set the mirroring used the next time the display is initialised, without sending any
+    /// commands
+    pub(crate) fn set_flip_config(&mut self, flip_x: bool, flip_y: bool) {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+    }
+
+    /// Get the parameters of the initialisation sequence
+    pub fn get_init_config(&self) -> InitConfig {
+        self.init_config
+    }
+
+    /// Set the parameters of the initialisation sequence, used the next time the display is
+    /// initialised
+    pub fn set_init_config(&mut self, init_config: InitConfig) {
+        self.init_config = init_config;
+    }
+
+    /// Segment remap and COM scan direction commands for the current rotation and mirroring
+    ///
+    /// Mirroring is done in hardware by toggling the direction of the panel axis the display axis
+    /// is mapped to, i.e. rows instead of columns for `Rotate90` and `Rotate270`.
+    fn orientation_commands(&self) -> [Command; 2] {
+        let (segment_remap, reverse_com_dir) = match self.display_rotation {
+            DisplayRotation::Rotate0 => (true, true),
+            DisplayRotation::Rotate90 => (false, true),
+            DisplayRotation::Rotate180 => (false, false),
+            DisplayRotation::Rotate270 => (true, false),
+        };
+        let (flip_columns, flip_rows) = match self.display_rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (self.flip_x, self.flip_y),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (self.flip_y, self.flip_x),
+        };
+
+        [
+            Command::SegmentRemap(segment_remap != flip_columns),
+            Command::ReverseComDir(reverse_com_dir != flip_rows),
+        ]
+    }
+
+    /// Commands sent by `init_column_mode`, in order
+    fn init_commands(&self) -> [Command; 16] {
+        let (_, display_height) = self.display_size.dimensions();
+        let [segment_remap, reverse_com_dir] = self.orientation_commands();
+        let config = &self.init_config;
+
+        [
+            Command::DisplayOn(false),
+            Command::DisplayClockDiv(config.oscillator_frequency, config.clock_divide_ratio),
+            Command::Multiplex(display_height - 1),
+            Command::DisplayOffset(self.display_size.display_offset()),
+            Command::StartLine(0),
+            // Display must be off when performing this command
+            Command::ChargePump(config.charge_pump),
+            Command::PumpVoltage(config.pump_voltage),
+            segment_remap,
+            reverse_com_dir,
+            Command::ComPinConfig(self.display_size.alternative_com_pins()),
+            Command::Contrast(config.contrast),
+            Command::PreChargePeriod(config.precharge_phase1, config.precharge_phase2),
+            Command::VcomhDeselect(config.vcomh_deselect),
+            Command::AllOn(false),
+            Command::Invert(false),
+            Command::DisplayOn(true),
+        ]
+    }
+
+    fn start_draw_area(&mut self, start: (u8, u8), end: (u8, u8)) {
+        self.draw_area_start = start;
+        self.draw_area_end = end;
+        self.draw_column = start.0;
+        self.draw_row = start.1;
+    }
+
+    /// Number of bytes of a `remaining` long buffer that can be sent before the end of the current
+    /// draw area row is reached
+    fn draw_span(&self, remaining: usize) -> usize {
+        ((self.draw_area_end.0 - self.draw_column) as usize).min(remaining)
+    }
+
+    /// Advance the draw position by `count` columns. Returns `true` when the draw address has
+    /// wrapped onto the next page and must be sent to the display again.
+    fn advance_draw_position(&mut self, count: usize) -> bool {
+        self.draw_column += count as u8;
+
+        if self.draw_column >= self.draw_area_end.0 {
+            self.draw_column = self.draw_area_start.0;
+
+            self.draw_row += 8;
+            if self.draw_row >= self.draw_area_end.1 {
+                self.draw_row = self.draw_area_start.1;
+            }
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn draw_address_commands(&self) -> [Command; 3] {
+        [
+            Command::PageAddress(self.draw_row.into()),
+            Command::ColumnAddressLow(0xF & self.draw_column),
+            Command::ColumnAddressHigh(0xF & (self.draw_column >> 4)),
+        ]
+    }
+}
+
+impl<DI> DisplayProperties<DI>
+where
+    DI: DisplayInterface,
+{
     /// Initialise the display in column mode (i.e. a byte walks down a column of 8 pixels) with
     /// column 0 on the left and column _(display_width - 1)_ on the right.
     pub fn init_column_mode(&mut self) -> Result<(), DI::Error> {
         self.iface.init()?;
-        // TODO: Break up into nice bits so display modes can pick whathever they need
-        let (_, display_height) = self.display_size.dimensions();
-        let display_rotation = self.display_rotation;
 
-        Command::DisplayOn(false).send(&mut self.iface)?;
-        Command::DisplayClockDiv(0x8, 0x0).send(&mut self.iface)?;
-        Command::Multiplex(display_height - 1).send(&mut self.iface)?;
-        Command::DisplayOffset(0).send(&mut self.iface)?;
-        Command::StartLine(0).send(&mut self.iface)?;
-        // TODO: Ability to turn charge pump on/off
-        // Display must be off when performing this command
-        Command::ChargePump(true).send(&mut self.iface)?;
-
-        self.set_rotation(display_rotation)?;
-
-        match self.display_size {
-            DisplaySize::Display128x32 => Command::ComPinConfig(false).send(&mut self.iface),
-            DisplaySize::Display128x64
-            | DisplaySize::Display128x64NoOffset
-            | DisplaySize::Display132x64 => Command::ComPinConfig(true).send(&mut self.iface),
-        }?;
-
-        Command::Contrast(0x80).send(&mut self.iface)?;
-        Command::PreChargePeriod(0x1, 0xF).send(&mut self.iface)?;
-        Command::VcomhDeselect(VcomhLevel::Auto).send(&mut self.iface)?;
-        Command::AllOn(false).send(&mut self.iface)?;
-        Command::Invert(false).send(&mut self.iface)?;
-        Command::DisplayOn(true).send(&mut self.iface)?;
+        for command in self.init_commands() {
+            command.send(&mut self.iface)?;
+        }
 
         Ok(())
     }
 
+    /// Initialise the display like [`init_column_mode`](Self::init_column_mode), following the
+    /// power-on sequence from the datasheet: the display is only turned on once the output of the
+    /// charge pump has settled, 100ms after enabling it.
+    pub fn init_column_mode_with_delay<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        self.iface.init()?;
+
+        let [setup @ .., display_on] = self.init_commands();
+
+        for command in setup {
+            command.send(&mut self.iface)?;
+        }
+
+        if self.init_config.charge_pump {
+            delay.delay_ms(CHARGE_PUMP_SETTLE_MS);
+        }
+
+        display_on.send(&mut self.iface)
+    }
+
+    /// Turn the display off and disable the charge pump, following the power-off sequence from
+    /// the datasheet. Display RAM is retained, so [`wake`](Self::wake) shows the same image again.
+    pub fn sleep(&mut self) -> Result<(), DI::Error> {
+        Command::DisplayOn(false).send(&mut self.iface)?;
+        Command::ChargePump(false).send(&mut self.iface)
+    }
+
+    /// Enable the charge pump, if configured, and turn the display back on. Use
+    /// [`wake_with_delay`](Self::wake_with_delay) to give the charge pump time to settle first.
+    pub fn wake(&mut self) -> Result<(), DI::Error> {
+        Command::ChargePump(self.init_config.charge_pump).send(&mut self.iface)?;
+        Command::DisplayOn(true).send(&mut self.iface)
+    }
+
+    /// Enable the charge pump, if configured, and turn the display back on once its output has
+    /// settled
+    pub fn wake_with_delay<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: DelayMs<u8>,
+    {
+        Command::ChargePump(self.init_config.charge_pump).send(&mut self.iface)?;
+
+        if self.init_config.charge_pump {
+            delay.delay_ms(CHARGE_PUMP_SETTLE_MS);
+        }
+
+        Command::DisplayOn(true).send(&mut self.iface)
+    }
+
     /// Set the position in the framebuffer of the display where any sent data should be
     /// drawn. This method can be used for changing the affected area on the screen as well
     /// as (re-)setting the start point of the next `draw` call.
     pub fn set_draw_area(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), DI::Error> {
-        self.draw_area_start = start;
-        self.draw_area_end = end;
-        self.draw_column = start.0;
-        self.draw_row = start.1;
+        self.start_draw_area(start, end);
 
         self.send_draw_address()
     }
@@ -92,79 +315,371 @@ where
     /// this method.
     pub fn draw(&mut self, mut buffer: &[u8]) -> Result<(), DI::Error> {
         while !buffer.is_empty() {
-            let count = self.draw_area_end.0 - self.draw_column;
-            self.iface.send_data(&buffer[..count as usize])?;
-            self.draw_column += count;
-
-            if self.draw_column >= self.draw_area_end.0 {
-                self.draw_column = self.draw_area_start.0;
-
-                self.draw_row += 8;
-                if self.draw_row >= self.draw_area_end.1 {
-                    self.draw_row = self.draw_area_start.1;
-                }
+            let count = self.draw_span(buffer.len());
+            self.iface.send_data(&buffer[..count])?;
 
+            if self.advance_draw_position(count) {
                 self.send_draw_address()?;
             }
 
-            buffer = &buffer[count as usize..];
+            buffer = &buffer[count..];
         }
 
         Ok(())
     }
 
     fn send_draw_address(&mut self) -> Result<(), DI::Error> {
-        Command::PageAddress(self.draw_row.into()).send(&mut self.iface)?;
-        Command::ColumnAddressLow(0xF & self.draw_column).send(&mut self.iface)?;
-        Command::ColumnAddressHigh(0xF & (self.draw_column >> 4)).send(&mut self.iface)
-    }
+        for command in self.draw_address_commands() {
+            command.send(&mut self.iface)?;
+        }
 
-    /// Get the configured display size
-    pub fn get_size(&self) -> DisplaySize {
-        self.display_size
+        Ok(())
     }
 
-    /// Get display dimensions, taking into account the current rotation of the display
-    pub fn get_dimensions(&self) -> (u8, u8) {
-        let (w, h) = self.display_size.dimensions();
+    /// Set the display rotation
+    pub fn set_rotation(&mut self, display_rotation: DisplayRotation) -> Result<(), DI::Error> {
+        self.display_rotation = display_rotation;
 
-        match self.display_rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (w, h),
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (h, w),
+        for command in self.orientation_commands() {
+            command.send(&mut self.iface)?;
         }
-    }
 
-    /// Get the display rotation
-    pub fn get_rotation(&self) -> DisplayRotation {
-        self.display_rotation
+        Ok(())
     }
 
-    /// Set the display rotation
-    pub fn set_rotation(&mut self, display_rotation: DisplayRotation) -> Result<(), DI::Error> {
-        self.display_rotation = display_rotation;
+    /// Mirror the display horizontally and/or vertically, independently of its rotation. Axes are
+    /// those of the rotated display.
+    pub fn set_flip(&mut self, flip_x: bool, flip_y: bool) -> Result<(), DI::Error> {
+        self.set_flip_config(flip_x, flip_y);
 
-        match display_rotation {
-            DisplayRotation::Rotate0 => {
-                Command::SegmentRemap(true).send(&mut self.iface)?;
-                Command::ReverseComDir(true).send(&mut self.iface)
-            }
-            DisplayRotation::Rotate90 => {
-                Command::SegmentRemap(false).send(&mut self.iface)?;
-                Command::ReverseComDir(true).send(&mut self.iface)
-            }
-            DisplayRotation::Rotate180 => {
-                Command::SegmentRemap(false).send(&mut self.iface)?;
-                Command::ReverseComDir(false).send(&mut self.iface)
-            }
-            DisplayRotation::Rotate270 => {
-                Command::SegmentRemap(true).send(&mut self.iface)?;
-                Command::ReverseComDir(false).send(&mut self.iface)
-            }
+        for command in self.orientation_commands() {
+            command.send(&mut self.iface)?;
         }
+
+        Ok(())
     }
 
     /// Set the display contrast
     pub fn set_contrast(&mut self, contrast: u8) -> Result<(), DI::Error> {
         Command::Contrast(contrast).send(&mut self.iface)
     }
+
+    /// Set the display RAM row shown on the first line of the panel, from 0-63
+    pub fn set_start_line(&mut self, line: u8) -> Result<(), DI::Error> {
+        Command::StartLine(line).send(&mut self.iface)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<DI> DisplayProperties<DI>
+where
+    DI: AsyncDisplayInterface,
+{
+    /// Asynchronous version of [`init_column_mode`](Self::init_column_mode)
+    pub async fn init_column_mode_async(&mut self) -> Result<(), DI::Error> {
+        self.iface.init().await?;
+
+        for command in self.init_commands() {
+            command.send_async(&mut self.iface).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Asynchronous version of [`set_draw_area`](Self::set_draw_area)
+    pub async fn set_draw_area_async(
+        &mut self,
+        start: (u8, u8),
+        end: (u8, u8),
+    ) -> Result<(), DI::Error> {
+        self.start_draw_area(start, end);
+
+        self.send_draw_address_async().await
+    }
+
+    /// Asynchronous version of [`draw`](Self::draw)
+    pub async fn draw_async(&mut self, mut buffer: &[u8]) -> Result<(), DI::Error> {
+        while !buffer.is_empty() {
+            let count = self.draw_span(buffer.len());
+            self.iface.send_data(&buffer[..count]).await?;
+
+            if self.advance_draw_position(count) {
+                self.send_draw_address_async().await?;
+            }
+
+            buffer = &buffer[count..];
+        }
+
+        Ok(())
+    }
+
+    async fn send_draw_address_async(&mut self) -> Result<(), DI::Error> {
+        for command in self.draw_address_commands() {
+            command.send_async(&mut self.iface).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Asynchronous version of [`set_rotation`](Self::set_rotation)
+    pub async fn set_rotation_async(
+        &mut self,
+        display_rotation: DisplayRotation,
+    ) -> Result<(), DI::Error> {
+        self.display_rotation = display_rotation;
+
+        for command in self.orientation_commands() {
+            command.send_async(&mut self.iface).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Asynchronous version of [`set_flip`](Self::set_flip)
+    pub async fn set_flip_async(&mut self, flip_x: bool, flip_y: bool) -> Result<(), DI::Error> {
+        self.set_flip_config(flip_x, flip_y);
+
+        for command in self.orientation_commands() {
+            command.send_async(&mut self.iface).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Asynchronous version of [`set_contrast`](Self::set_contrast)
+    pub async fn set_contrast_async(&mut self, contrast: u8) -> Result<(), DI::Error> {
+        Command::Contrast(contrast)
+            .send_async(&mut self.iface)
+            .await
+    }
+
+    /// Asynchronous version of [`set_start_line`](Self::set_start_line)
+    pub async fn set_start_line_async(&mut self, line: u8) -> Result<(), DI::Error> {
+        Command::StartLine(line).send_async(&mut self.iface).await
+    }
+
+    /// Asynchronous version of [`init_column_mode_with_delay`](Self::init_column_mode_with_delay)
+    pub async fn init_column_mode_with_delay_async<DELAY>(
+        &mut self,
+        delay: &mut DELAY,
+    ) -> Result<(), DI::Error>
+    where
+        DELAY: embedded_hal_async::delay::DelayNs,
+    {
+        self.iface.init().await?;
+
+        let [setup @ .., display_on] = self.init_commands();
+
+        for command in setup {
+            command.send_async(&mut self.iface).await?;
+        }
+
+        if self.init_config.charge_pump {
+            delay.delay_ms(CHARGE_PUMP_SETTLE_MS.into()).await;
+        }
+
+        display_on.send_async(&mut self.iface).await
+    }
+
+    /// Asynchronous version of [`sleep`](Self::sleep)
+    pub async fn sleep_async(&mut self) -> Result<(), DI::Error> {
+        Command::DisplayOn(false)
+            .send_async(&mut self.iface)
+            .await?;
+        Command::ChargePump(false).send_async(&mut self.iface).await
+    }
+
+    /// Asynchronous version of [`wake`](Self::wake)
+    pub async fn wake_async(&mut self) -> Result<(), DI::Error> {
+        Command::ChargePump(self.init_config.charge_pump)
+            .send_async(&mut self.iface)
+            .await?;
+        Command::DisplayOn(true).send_async(&mut self.iface).await
+    }
+
+    /// Asynchronous version of [`wake_with_delay`](Self::wake_with_delay)
+    pub async fn wake_with_delay_async<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), DI::Error>
+    where
+        DELAY: embedded_hal_async::delay::DelayNs,
+    {
+        Command::ChargePump(self.init_config.charge_pump)
+            .send_async(&mut self.iface)
+            .await?;
+
+        if self.init_config.charge_pump {
+            delay.delay_ms(CHARGE_PUMP_SETTLE_MS.into()).await;
+        }
+
+        Command::DisplayOn(true).send_async(&mut self.iface).await
+    }
+}
+
+impl<DI, CommE, PinE> DisplayProperties<DI>
+where
+    DI: DisplayInterface<Error = Error<CommE, PinE>>,
+{
+    /// Send a single command to the display. Returns [`Error::InvalidArgument`] without sending
+    /// anything if the command's arguments are out of range, see [`Command::is_valid`].
+    pub fn send_command(&mut self, command: Command) -> Result<(), DI::Error> {
+        if !command.is_valid() {
+            return Err(Error::InvalidArgument);
+        }
+
+        command.send(&mut self.iface)
+    }
+
+    /// Reset the display by pulsing its reset pin. Errors setting the pin are reported as
+    /// [`Error::Pin`] with the error type of the reset pin, which can be any pin. Use
+    /// [`Builder::with_reset`](crate::Builder::with_reset) to reset the display every time it is
+    /// initialised instead.
+    pub fn reset<RST, DELAY>(
+        &mut self,
+        rst: &mut RST,
+        delay: &mut DELAY,
+    ) -> Result<(), Error<CommE, RST::Error>>
+    where
+        RST: OutputPin,
+        DELAY: DelayMs<u8>,
+    {
+        reset::pulse(rst, delay, reset::RESET_PULSE_MS, 0).map_err(Error::Pin)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<DI, CommE, PinE> DisplayProperties<DI>
+where
+    DI: AsyncDisplayInterface<Error = Error<CommE, PinE>>,
+{
+    /// Asynchronous version of [`send_command`](Self::send_command)
+    pub async fn send_command_async(&mut self, command: Command) -> Result<(), DI::Error> {
+        if !command.is_valid() {
+            return Err(Error::InvalidArgument);
+        }
+
+        command.send_async(&mut self.iface).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        command::VcomhLevel,
+        interface::I2cInterface,
+        test_helpers::{I2cStub, Recorder, Transaction},
+    };
+
+    #[test]
+    fn init_sequence_uses_config() {
+        let mut properties = DisplayProperties::new(
+            I2cStub,
+            DisplaySize::Display128x64,
+            DisplayRotation::Rotate0,
+        );
+
+        let commands = properties.init_commands();
+        assert_eq!(commands[1], Command::DisplayClockDiv(0x8, 0x0));
+        assert_eq!(commands[5], Command::ChargePump(true));
+        assert_eq!(commands[12], Command::VcomhDeselect(VcomhLevel::Auto));
+
+        properties.set_init_config(
+            InitConfig::new()
+                .with_display_clock(0xF, 0x1)
+                .with_charge_pump(false)
+                .with_contrast(0x10)
+                .with_precharge_period(0x2, 0x2)
+                .with_vcomh_deselect(VcomhLevel::V065),
+        );
+
+        let commands = properties.init_commands();
+        assert_eq!(commands[1], Command::DisplayClockDiv(0xF, 0x1));
+        assert_eq!(commands[5], Command::ChargePump(false));
+        assert_eq!(commands[10], Command::Contrast(0x10));
+        assert_eq!(commands[11], Command::PreChargePeriod(0x2, 0x2));
+        assert_eq!(commands[12], Command::VcomhDeselect(VcomhLevel::V065));
+        assert!(commands.iter().all(Command::is_valid));
+    }
+
+    #[test]
+    fn power_sequence_waits_for_charge_pump() {
+        let recorder = Recorder::new();
+        let mut properties = DisplayProperties::new(
+            I2cInterface::new(recorder.i2c(), 0x3c),
+            DisplaySize::Display128x64,
+            DisplayRotation::Rotate0,
+        );
+        let command = |bytes| Transaction::I2cWrite { addr: 0x3c, bytes };
+
+        properties
+            .init_column_mode_with_delay(&mut recorder.delay())
+            .unwrap();
+
+        // The display is turned on last, after waiting once for the charge pump
+        let log = recorder.log();
+        assert_eq!(log.len(), 17);
+        assert_eq!(
+            log.transactions()
+                .filter(|transaction| matches!(transaction, Transaction::Delay { .. }))
+                .count(),
+            1
+        );
+        assert!(log
+            .transactions()
+            .skip(15)
+            .eq([Transaction::Delay { ms: 100 }, command(&[0x00, 0xAF])]));
+        drop(log);
+
+        recorder.clear();
+        properties.sleep().unwrap();
+        assert!(recorder
+            .log()
+            .transactions()
+            .eq([command(&[0x00, 0xAE]), command(&[0x00, 0xAD, 0x8A])]));
+
+        recorder.clear();
+        properties.wake_with_delay(&mut recorder.delay()).unwrap();
+        assert!(recorder.log().transactions().eq([
+            command(&[0x00, 0xAD, 0x8B]),
+            Transaction::Delay { ms: 100 },
+            command(&[0x00, 0xAF])
+        ]));
+    }
+
+    #[test]
+    fn init_sequence_is_sent_over_i2c() {
+        let recorder = Recorder::new();
+        let mut properties = DisplayProperties::new(
+            I2cInterface::new(recorder.i2c(), 0x3c),
+            DisplaySize::Display128x64,
+            DisplayRotation::Rotate0,
+        );
+
+        properties.init_column_mode().unwrap();
+
+        let expected: [&[u8]; 16] = [
+            &[0x00, 0xAE],
+            &[0x00, 0xD5, 0x80],
+            &[0x00, 0xA8, 0x3F],
+            &[0x00, 0xD3, 0x00],
+            &[0x00, 0x40],
+            &[0x00, 0xAD, 0x8B],
+            &[0x00, 0x32],
+            &[0x00, 0xA1],
+            &[0x00, 0xC8],
+            &[0x00, 0xDA, 0x12],
+            &[0x00, 0x81, 0x80],
+            &[0x00, 0xD9, 0xF1],
+            &[0x00, 0xDB, 0x40],
+            &[0x00, 0xA4],
+            &[0x00, 0xA6],
+            &[0x00, 0xAF],
+        ];
+
+        let log = recorder.log();
+        assert_eq!(log.len(), expected.len());
+
+        for (transaction, bytes) in log.transactions().zip(expected) {
+            assert_eq!(transaction, Transaction::I2cWrite { addr: 0x3c, bytes });
+        }
+    }
 }
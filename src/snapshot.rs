@@ -0,0 +1,487 @@
+//! Framebuffer snapshots for golden image tests
+//!
+//! An [`Image`] holds the pixels of a display as seen by the viewer, i.e. with the display
+//! rotation applied. Snapshots are taken from [`GraphicsMode`](crate::mode::GraphicsMode) with
+//! `snapshot`, or from the [`Emulator`](crate::emulator::Emulator), and can be exported as PBM or
+//! PNG and compared against a stored golden image:
+//!
+//! ```rust
+//! use sh1106::{prelude::*, Builder};
+//! # let i2c = sh1106::test_helpers::I2cStub;
+//!
+//! let mut display: GraphicsMode<_> = Builder::new().connect_i2c(i2c).into();
+//!
+//! display.set_pixel(1, 1, 1);
+//! display.set_pixel(2, 1, 1);
+//!
+//! let snapshot = display.snapshot();
+//! # let golden = snapshot.to_png();
+//! // let golden = std::fs::read("tests/golden/pixels.png").unwrap();
+//!
+//! snapshot.compare(&golden).unwrap();
+//! ```
+//!
+//! Lit pixels are white in both formats. Only available with the `std` feature enabled.
+
+use core::fmt;
+use std::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Monochrome image of the display as seen by the viewer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<bool>,
+}
+
+impl Image {
+    /// Create an image with all pixels off
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![false; width as usize * height as usize],
+        }
+    }
+
+    /// Create an image by calling `pixel` for every coordinate
+    pub fn from_fn(width: u32, height: u32, mut pixel: impl FnMut(u32, u32) -> bool) -> Self {
+        let mut image = Self::new(width, height);
+
+        for y in 0..height {
+            for x in 0..width {
+                image.set_pixel(x, y, pixel(x, y));
+            }
+        }
+
+        image
+    }
+
+    /// Width and height of the image
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Whether the pixel at the given coordinates is lit. Pixels outside of the image are off.
+    pub fn pixel(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.pixels[self.index(x, y)]
+    }
+
+    /// Turn a pixel on or off. Pixels outside of the image are ignored.
+    pub fn set_pixel(&mut self, x: u32, y: u32, on: bool) {
+        if x < self.width && y < self.height {
+            let index = self.index(x, y);
+            self.pixels[index] = on;
+        }
+    }
+
+    /// Encode the image as a binary (P4) PBM file
+    pub fn to_pbm(&self) -> Vec<u8> {
+        let mut pbm = format!("P4\n{} {}\n", self.width, self.height).into_bytes();
+
+        // PBM uses 1 for black, so lit pixels are stored as 0
+        pbm.extend(self.packed_rows(false));
+
+        pbm
+    }
+
+    /// Encode the image as a 1 bit grayscale PNG file
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut png = Vec::new();
+
+        {
+            let mut encoder = png::Encoder::new(&mut png, self.width, self.height);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::One);
+
+            // Writing to a `Vec` can't fail and the data length matches the header
+            let mut writer = encoder.write_header().expect("PNG header");
+            writer
+                .write_image_data(&self.packed_rows(true))
+                .expect("PNG data");
+        }
+
+        png
+    }
+
+    /// Decode a PBM (P1 or P4) or PNG file, detected from its contents. Grayscale and colour PNGs
+    /// are thresholded at half brightness.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.starts_with(b"P1") || bytes.starts_with(b"P4") {
+            Self::from_pbm(bytes)
+        } else {
+            Self::from_png(bytes)
+        }
+    }
+
+    /// Decode a PBM file in plain (P1) or binary (P4) format
+    pub fn from_pbm(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut header = PbmHeader { bytes, pos: 0 };
+
+        let magic = header.token()?;
+        let width = header.number()?;
+        let height = header.number()?;
+
+        // Every pixel takes at least a character in P1 and a bit in P4 files, so the image can't
+        // be larger than the remaining data allows. Checking this first keeps bogus dimensions
+        // from allocating huge images.
+        let remaining = bytes.len() - header.pos;
+        let required = match magic {
+            b"P1" => (width as usize).checked_mul(height as usize),
+            b"P4" => row_bytes(width).checked_mul(height as usize),
+            _ => return Err(DecodeError::new("not a P1 or P4 PBM file")),
+        };
+
+        match required {
+            Some(required) if required <= remaining => {}
+            _ => return Err(DecodeError::new("truncated PBM data")),
+        }
+
+        let mut image = Self::new(width, height);
+
+        match magic {
+            b"P1" => {
+                for index in 0..image.pixels.len() {
+                    match header.bit()? {
+                        b'0' => image.pixels[index] = true,
+                        _ => image.pixels[index] = false,
+                    }
+                }
+            }
+            b"P4" => {
+                // A single whitespace byte separates the header from the data
+                let data = bytes
+                    .get(header.pos + 1..)
+                    .ok_or_else(|| DecodeError::new("truncated PBM data"))?;
+                let stride = row_bytes(width);
+
+                if data.len() < stride * height as usize {
+                    return Err(DecodeError::new("truncated PBM data"));
+                }
+
+                for y in 0..height {
+                    for x in 0..width {
+                        let byte = data[y as usize * stride + x as usize / 8];
+                        let black = byte & (0x80 >> (x % 8)) != 0;
+
+                        image.set_pixel(x, y, !black);
+                    }
+                }
+            }
+            _ => return Err(DecodeError::new("not a P1 or P4 PBM file")),
+        }
+
+        Ok(image)
+    }
+
+    /// Decode a PNG file. Grayscale and colour images are thresholded at half brightness.
+    pub fn from_png(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+        let mut reader = decoder.read_info().map_err(DecodeError::png)?;
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data).map_err(DecodeError::png)?;
+
+        let channels = info.color_type.samples();
+        let color = match info.color_type {
+            png::ColorType::Rgb | png::ColorType::Rgba => 3,
+            _ => 1,
+        };
+
+        Ok(Self::from_fn(info.width, info.height, |x, y| {
+            let start = y as usize * info.line_size + x as usize * channels;
+            let sum: u32 = data[start..start + color]
+                .iter()
+                .map(|&v| u32::from(v))
+                .sum();
+
+            sum / color as u32 >= 0x80
+        }))
+    }
+
+    /// Compare against a golden image encoded as PBM or PNG, see [`decode`](Self::decode)
+    pub fn compare(&self, golden: &[u8]) -> Result<(), Mismatch> {
+        let golden = Self::decode(golden).map_err(Mismatch::Invalid)?;
+
+        self.compare_image(&golden)
+    }
+
+    /// Compare against a golden image stored in a PBM or PNG file
+    pub fn compare_file(&self, path: impl AsRef<std::path::Path>) -> Result<(), Mismatch> {
+        let golden = std::fs::read(path)
+            .map_err(|error| Mismatch::Invalid(DecodeError::new(&error.to_string())))?;
+
+        self.compare(&golden)
+    }
+
+    /// Compare against another image
+    pub fn compare_image(&self, golden: &Image) -> Result<(), Mismatch> {
+        if self.dimensions() != golden.dimensions() {
+            return Err(Mismatch::Size {
+                expected: golden.dimensions(),
+                actual: self.dimensions(),
+            });
+        }
+
+        let diff = Self::from_fn(self.width, self.height, |x, y| {
+            self.pixel(x, y) != golden.pixel(x, y)
+        });
+        let count = diff.pixels.iter().filter(|&&differs| differs).count();
+
+        match diff.pixels.iter().position(|&differs| differs) {
+            None => Ok(()),
+            Some(index) => Err(Mismatch::Pixels {
+                count,
+                first: (index as u32 % self.width, index as u32 / self.width),
+                diff,
+            }),
+        }
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    /// Pixels packed into bytes, most significant bit first, with each row padded to a whole byte
+    fn packed_rows(&self, lit: bool) -> Vec<u8> {
+        let stride = row_bytes(self.width);
+        let mut packed = vec![0; stride * self.height as usize];
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.pixel(x, y) == lit {
+                    packed[y as usize * stride + x as usize / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+
+        packed
+    }
+}
+
+impl fmt::Display for Image {
+    /// Render the image as text, one line per row with `#` for lit and `.` for unlit pixels
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                f.write_str(if self.pixel(x, y) { "#" } else { "." })?;
+            }
+
+            f.write_str("\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Error decoding an image file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    message: String,
+}
+
+impl DecodeError {
+    fn new(message: &str) -> Self {
+        Self {
+            message: message.to_string(),
+        }
+    }
+
+    fn png(error: png::DecodingError) -> Self {
+        Self::new(&error.to_string())
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Difference between an image and its golden image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The golden image couldn't be read or decoded
+    Invalid(DecodeError),
+    /// The images have different dimensions
+    Size {
+        /// Dimensions of the golden image
+        expected: (u32, u32),
+        /// Dimensions of the compared image
+        actual: (u32, u32),
+    },
+    /// Some pixels differ
+    Pixels {
+        /// Number of differing pixels
+        count: usize,
+        /// Coordinates of the first differing pixel, in row order
+        first: (u32, u32),
+        /// Image with the differing pixels lit
+        diff: Image,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Invalid(error) => write!(f, "invalid golden image: {}", error),
+            Mismatch::Size { expected, actual } => write!(
+                f,
+                "image is {}x{}, golden image is {}x{}",
+                actual.0, actual.1, expected.0, expected.1
+            ),
+            Mismatch::Pixels { count, first, diff } => write!(
+                f,
+                "{} pixels differ, starting at ({}, {}):\n{}",
+                count, first.0, first.1, diff
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+/// Number of bytes in a row of `width` packed pixels
+fn row_bytes(width: u32) -> usize {
+    (width as usize + 7) >> 3
+}
+
+/// Tokenizer for the text header of PBM files
+struct PbmHeader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PbmHeader<'a> {
+    /// Skip whitespace and comments
+    fn skip(&mut self) {
+        while let Some(&byte) = self.bytes.get(self.pos) {
+            if byte == b'#' {
+                while self.bytes.get(self.pos).is_some_and(|&b| b != b'\n') {
+                    self.pos += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self) -> Result<&'a [u8], DecodeError> {
+        self.skip();
+
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| !b.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+
+        if start == self.pos {
+            return Err(DecodeError::new("truncated PBM header"));
+        }
+
+        Ok(&self.bytes[start..self.pos])
+    }
+
+    fn number(&mut self) -> Result<u32, DecodeError> {
+        core::str::from_utf8(self.token()?)
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| DecodeError::new("invalid PBM dimensions"))
+    }
+
+    /// Next `0` or `1` of a plain PBM file
+    fn bit(&mut self) -> Result<u8, DecodeError> {
+        self.skip();
+
+        match self.bytes.get(self.pos) {
+            Some(&bit @ (b'0' | b'1')) => {
+                self.pos += 1;
+                Ok(bit)
+            }
+            _ => Err(DecodeError::new("truncated PBM data")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> Image {
+        Image::from_fn(11, 3, |x, y| (x + y) % 2 == 0)
+    }
+
+    #[test]
+    fn pbm_round_trip() {
+        let image = checkerboard();
+        let pbm = image.to_pbm();
+
+        assert!(pbm.starts_with(b"P4\n11 3\n"));
+        assert_eq!(pbm.len(), 8 + 2 * 3);
+        assert_eq!(Image::decode(&pbm), Ok(image));
+    }
+
+    #[test]
+    fn plain_pbm_is_decoded() {
+        let image = Image::decode(b"P1\n# comment\n3 2\n0 1 0\n1 1 0\n").unwrap();
+
+        assert_eq!(image.to_string(), "#.#\n..#\n");
+    }
+
+    #[test]
+    fn truncated_pbm_is_rejected() {
+        let truncated = Err(DecodeError::new("truncated PBM data"));
+
+        assert_eq!(Image::decode(b"P4\n8 2"), truncated);
+        assert_eq!(Image::decode(b"P4\n8 2\n\x00"), truncated);
+        assert_eq!(Image::decode(b"P4 4000000000 4000000000\n"), truncated);
+        assert_eq!(Image::decode(b"P1\n3 2\n0 1 0\n1 1"), truncated);
+        assert_eq!(Image::decode(b"P1\n4000000000 4000000000\n0"), truncated);
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let image = checkerboard();
+
+        assert_eq!(Image::decode(&image.to_png()), Ok(image));
+    }
+
+    #[test]
+    fn compare_reports_differences() {
+        let image = checkerboard();
+        let mut changed = image.clone();
+        changed.set_pixel(4, 2, !image.pixel(4, 2));
+        changed.set_pixel(7, 2, !image.pixel(7, 2));
+
+        assert_eq!(image.compare(&image.to_png()), Ok(()));
+
+        match changed.compare(&image.to_pbm()) {
+            Err(Mismatch::Pixels { count, first, .. }) => {
+                assert_eq!(count, 2);
+                assert_eq!(first, (4, 2));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        assert_eq!(
+            Image::new(2, 2).compare_image(&image),
+            Err(Mismatch::Size {
+                expected: (11, 3),
+                actual: (2, 2)
+            })
+        );
+    }
+}
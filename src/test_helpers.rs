@@ -1,13 +1,45 @@
 //! Helpers for use in examples and tests
+//!
+//! The stubs accept and discard everything, which is enough for examples. The [`Recorder`] and
+//! its mocks capture every transaction on the wire instead, so tests can assert the exact bytes
+//! sent to the display:
+//!
+//! ```rust
+//! use sh1106::{
+//!     prelude::*,
+//!     test_helpers::{Recorder, Transaction},
+//!     Builder,
+//! };
+//!
+//! let recorder = Recorder::new();
+//! let mut display: GraphicsMode<_> = Builder::new().connect_i2c(recorder.i2c()).into();
+//!
+//! display.set_contrast(0x40).unwrap();
+//!
+//! assert_eq!(
+//!     recorder.log().transactions().last(),
+//!     Some(Transaction::I2cWrite {
+//!         addr: 0x3c,
+//!         bytes: &[0x00, 0x81, 0x40]
+//!     })
+//! );
+//! ```
 
+use core::{
+    cell::{Ref, RefCell},
+    convert::Infallible,
+};
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
 use embedded_hal::{
     blocking::{
+        delay::DelayMs,
         i2c,
         spi::{self, Transfer},
     },
     digital::v2::OutputPin,
 };
 
+/// SPI peripheral discarding all writes
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct SpiStub;
@@ -28,22 +60,33 @@ impl Transfer<u8> for SpiStub {
     }
 }
 
+/// Output pin ignoring all level changes
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct PinStub;
 
 impl OutputPin for PinStub {
-    type Error = ();
+    type Error = Infallible;
 
-    fn set_high(&mut self) -> Result<(), ()> {
+    fn set_high(&mut self) -> Result<(), Infallible> {
         Ok(())
     }
 
-    fn set_low(&mut self) -> Result<(), ()> {
+    fn set_low(&mut self) -> Result<(), Infallible> {
         Ok(())
     }
 }
 
+/// Delay returning immediately
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct DelayStub;
+
+impl DelayMs<u8> for DelayStub {
+    fn delay_ms(&mut self, _ms: u8) {}
+}
+
+/// I2C peripheral discarding all writes
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub struct I2cStub;
@@ -55,3 +98,537 @@ impl i2c::Write for I2cStub {
         Ok(())
     }
 }
+
+impl embedded_hal_1::spi::ErrorType for SpiStub {
+    type Error = Infallible;
+}
+
+impl embedded_hal_1::spi::SpiDevice for SpiStub {
+    fn transaction(
+        &mut self,
+        _operations: &mut [embedded_hal_1::spi::Operation<'_, u8>],
+    ) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl embedded_hal_1::digital::ErrorType for PinStub {
+    type Error = Infallible;
+}
+
+impl embedded_hal_1::digital::OutputPin for PinStub {
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl embedded_hal_1::i2c::ErrorType for I2cStub {
+    type Error = Infallible;
+}
+
+impl embedded_hal_1::i2c::I2c for I2cStub {
+    fn transaction(
+        &mut self,
+        _address: u8,
+        _operations: &mut [embedded_hal_1::i2c::Operation<'_>],
+    ) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::spi::SpiDevice for SpiStub {
+    async fn transaction(
+        &mut self,
+        _operations: &mut [embedded_hal_1::spi::Operation<'_, u8>],
+    ) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::i2c::I2c for I2cStub {
+    async fn transaction(
+        &mut self,
+        _address: u8,
+        _operations: &mut [embedded_hal_1::i2c::Operation<'_>],
+    ) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl embedded_hal_1::delay::DelayNs for DelayStub {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for DelayStub {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+/// Run `future` to completion on the current thread, for tests of the asynchronous API against
+/// the stubs and mocks, which never have to wait
+#[cfg(all(test, feature = "async"))]
+#[allow(unsafe_code)]
+pub(crate) fn block_on<F: core::future::Future>(future: F) -> F::Output {
+    use core::{
+        pin::pin,
+        ptr,
+        task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    };
+
+    fn raw_waker() -> RawWaker {
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(|_| raw_waker(), |_| {}, |_| {}, |_| {});
+
+    // Safety: the vtable functions ignore the data pointer and do nothing
+    let waker = unsafe { Waker::from_raw(raw_waker()) };
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// `display-interface` implementation discarding all commands and data
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct DataCommandStub;
+
+impl WriteOnlyDataCommand for DataCommandStub {
+    fn send_commands(&mut self, _cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        Ok(())
+    }
+
+    fn send_data(&mut self, _buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        Ok(())
+    }
+}
+
+/// Maximum number of bytes recorded by a [`Recorder`]
+const DATA_CAPACITY: usize = 8192;
+
+/// Maximum number of transactions recorded by a [`Recorder`]
+const TRANSACTION_CAPACITY: usize = 512;
+
+/// Pins that can be recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinId {
+    /// Data/Command select pin
+    Dc,
+    /// Chip Select pin
+    Cs,
+    /// Reset pin
+    Reset,
+}
+
+/// A transaction captured by a [`Recorder`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transaction<'a> {
+    /// I2C write of `bytes` to the device at `addr`
+    I2cWrite {
+        /// 7 bit device address
+        addr: u8,
+        /// Bytes written, including the control byte
+        bytes: &'a [u8],
+    },
+    /// SPI write of `bytes`, along with the levels of the DC and CS pins at the time
+    SpiWrite {
+        /// Level of the Data/Command pin, high for data
+        dc: bool,
+        /// Level of the Chip Select pin, low when selected
+        cs: bool,
+        /// Bytes written
+        bytes: &'a [u8],
+    },
+    /// A pin was set high or low
+    Pin {
+        /// The pin that changed
+        pin: PinId,
+        /// New level of the pin
+        high: bool,
+    },
+    /// A delay of `ms` milliseconds, rounded up to whole milliseconds
+    Delay {
+        /// Length of the delay in milliseconds
+        ms: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Entry {
+    I2cWrite {
+        addr: u8,
+        start: usize,
+        end: usize,
+    },
+    SpiWrite {
+        dc: bool,
+        cs: bool,
+        start: usize,
+        end: usize,
+    },
+    Pin {
+        pin: PinId,
+        high: bool,
+    },
+    Delay {
+        ms: u32,
+    },
+}
+
+/// Transactions captured by a [`Recorder`]
+pub struct Log {
+    data: [u8; DATA_CAPACITY],
+    data_len: usize,
+    entries: [Entry; TRANSACTION_CAPACITY],
+    entry_count: usize,
+    dc: bool,
+    cs: bool,
+}
+
+impl Log {
+    /// Iterate over all transactions in the order they happened
+    pub fn transactions(&self) -> impl Iterator<Item = Transaction<'_>> + '_ {
+        self.entries[..self.entry_count]
+            .iter()
+            .map(move |entry| match *entry {
+                Entry::I2cWrite { addr, start, end } => Transaction::I2cWrite {
+                    addr,
+                    bytes: &self.data[start..end],
+                },
+                Entry::SpiWrite { dc, cs, start, end } => Transaction::SpiWrite {
+                    dc,
+                    cs,
+                    bytes: &self.data[start..end],
+                },
+                Entry::Pin { pin, high } => Transaction::Pin { pin, high },
+                Entry::Delay { ms } => Transaction::Delay { ms },
+            })
+    }
+
+    /// Number of recorded transactions
+    pub fn len(&self) -> usize {
+        self.entry_count
+    }
+
+    /// Whether no transactions have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.entry_count == 0
+    }
+
+    fn push(&mut self, entry: Entry) {
+        assert!(
+            self.entry_count < TRANSACTION_CAPACITY,
+            "recorder transaction capacity exceeded"
+        );
+
+        self.entries[self.entry_count] = entry;
+        self.entry_count += 1;
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) -> (usize, usize) {
+        let start = self.data_len;
+        let end = start + bytes.len();
+
+        assert!(end <= DATA_CAPACITY, "recorder data capacity exceeded");
+
+        self.data[start..end].copy_from_slice(bytes);
+        self.data_len = end;
+
+        (start, end)
+    }
+
+    fn i2c_write(&mut self, addr: u8, bytes: &[u8]) {
+        let (start, end) = self.push_bytes(bytes);
+
+        self.push(Entry::I2cWrite { addr, start, end });
+    }
+
+    fn spi_write(&mut self, bytes: &[u8]) {
+        let (start, end) = self.push_bytes(bytes);
+        let (dc, cs) = (self.dc, self.cs);
+
+        self.push(Entry::SpiWrite { dc, cs, start, end });
+    }
+
+    fn set_pin(&mut self, pin: PinId, high: bool) {
+        match pin {
+            PinId::Dc => self.dc = high,
+            PinId::Cs => self.cs = high,
+            PinId::Reset => {}
+        }
+
+        self.push(Entry::Pin { pin, high });
+    }
+}
+
+/// Records every transaction of the I2C, SPI, pin and delay mocks created from it
+///
+/// All pins start out low. Recording panics once more than 8192 bytes or 512 transactions have
+/// been captured; use [`clear`](Self::clear) between steps of longer tests.
+pub struct Recorder {
+    log: RefCell<Log>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recorder {
+    /// Create an empty recorder
+    pub fn new() -> Self {
+        Self {
+            log: RefCell::new(Log {
+                data: [0; DATA_CAPACITY],
+                data_len: 0,
+                entries: [Entry::Pin {
+                    pin: PinId::Reset,
+                    high: false,
+                }; TRANSACTION_CAPACITY],
+                entry_count: 0,
+                dc: false,
+                cs: false,
+            }),
+        }
+    }
+
+    /// Create an I2C peripheral recording into this recorder
+    pub fn i2c(&self) -> I2cMock<'_> {
+        I2cMock { recorder: self }
+    }
+
+    /// Create an SPI peripheral recording into this recorder
+    pub fn spi(&self) -> SpiMock<'_> {
+        SpiMock { recorder: self }
+    }
+
+    /// Create an output pin recording into this recorder
+    pub fn pin(&self, pin: PinId) -> PinMock<'_> {
+        PinMock {
+            recorder: self,
+            pin,
+        }
+    }
+
+    /// Create a delay recording into this recorder
+    pub fn delay(&self) -> DelayMock<'_> {
+        DelayMock { recorder: self }
+    }
+
+    /// Access the transactions recorded so far
+    pub fn log(&self) -> Ref<'_, Log> {
+        self.log.borrow()
+    }
+
+    /// Forget all transactions recorded so far. Pin levels are kept.
+    pub fn clear(&self) {
+        let mut log = self.log.borrow_mut();
+
+        log.data_len = 0;
+        log.entry_count = 0;
+    }
+}
+
+/// I2C peripheral recording all writes, created with [`Recorder::i2c`]
+#[derive(Clone, Copy)]
+pub struct I2cMock<'a> {
+    recorder: &'a Recorder,
+}
+
+impl i2c::Write for I2cMock<'_> {
+    type Error = Infallible;
+
+    fn write(&mut self, addr: u8, buf: &[u8]) -> Result<(), Infallible> {
+        self.recorder.log.borrow_mut().i2c_write(addr, buf);
+
+        Ok(())
+    }
+}
+
+impl embedded_hal_1::i2c::ErrorType for I2cMock<'_> {
+    type Error = Infallible;
+}
+
+impl embedded_hal_1::i2c::I2c for I2cMock<'_> {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [embedded_hal_1::i2c::Operation<'_>],
+    ) -> Result<(), Infallible> {
+        let mut log = self.recorder.log.borrow_mut();
+
+        for operation in operations {
+            if let embedded_hal_1::i2c::Operation::Write(bytes) = operation {
+                log.i2c_write(address, bytes);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::i2c::I2c for I2cMock<'_> {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [embedded_hal_1::i2c::Operation<'_>],
+    ) -> Result<(), Infallible> {
+        embedded_hal_1::i2c::I2c::transaction(self, address, operations)
+    }
+}
+
+/// SPI peripheral recording all writes, created with [`Recorder::spi`]
+///
+/// As an `embedded-hal` 1.0 [`SpiDevice`](embedded_hal_1::spi::SpiDevice), it frames every
+/// transaction by recording the CS pin going low and back high.
+#[derive(Clone, Copy)]
+pub struct SpiMock<'a> {
+    recorder: &'a Recorder,
+}
+
+impl spi::Write<u8> for SpiMock<'_> {
+    type Error = Infallible;
+
+    fn write(&mut self, buf: &[u8]) -> Result<(), Infallible> {
+        self.recorder.log.borrow_mut().spi_write(buf);
+
+        Ok(())
+    }
+}
+
+impl Transfer<u8> for SpiMock<'_> {
+    type Error = Infallible;
+
+    fn transfer<'a>(&mut self, buf: &'a mut [u8]) -> Result<&'a [u8], Infallible> {
+        self.recorder.log.borrow_mut().spi_write(buf);
+
+        Ok(buf)
+    }
+}
+
+impl embedded_hal_1::spi::ErrorType for SpiMock<'_> {
+    type Error = Infallible;
+}
+
+impl embedded_hal_1::spi::SpiDevice for SpiMock<'_> {
+    fn transaction(
+        &mut self,
+        operations: &mut [embedded_hal_1::spi::Operation<'_, u8>],
+    ) -> Result<(), Infallible> {
+        let mut log = self.recorder.log.borrow_mut();
+
+        log.set_pin(PinId::Cs, false);
+
+        for operation in operations {
+            match operation {
+                embedded_hal_1::spi::Operation::Write(bytes) => log.spi_write(bytes),
+                embedded_hal_1::spi::Operation::Transfer(_, bytes) => log.spi_write(bytes),
+                embedded_hal_1::spi::Operation::TransferInPlace(bytes) => log.spi_write(bytes),
+                _ => {}
+            }
+        }
+
+        log.set_pin(PinId::Cs, true);
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::spi::SpiDevice for SpiMock<'_> {
+    async fn transaction(
+        &mut self,
+        operations: &mut [embedded_hal_1::spi::Operation<'_, u8>],
+    ) -> Result<(), Infallible> {
+        embedded_hal_1::spi::SpiDevice::transaction(self, operations)
+    }
+}
+
+/// Output pin recording all level changes, created with [`Recorder::pin`]
+#[derive(Clone, Copy)]
+pub struct PinMock<'a> {
+    recorder: &'a Recorder,
+    pin: PinId,
+}
+
+impl OutputPin for PinMock<'_> {
+    type Error = Infallible;
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.recorder.log.borrow_mut().set_pin(self.pin, true);
+
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.recorder.log.borrow_mut().set_pin(self.pin, false);
+
+        Ok(())
+    }
+}
+
+impl embedded_hal_1::digital::ErrorType for PinMock<'_> {
+    type Error = Infallible;
+}
+
+impl embedded_hal_1::digital::OutputPin for PinMock<'_> {
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        OutputPin::set_high(self)
+    }
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        OutputPin::set_low(self)
+    }
+}
+
+/// Delay recording its length instead of waiting, created with [`Recorder::delay`]
+#[derive(Clone, Copy)]
+pub struct DelayMock<'a> {
+    recorder: &'a Recorder,
+}
+
+impl DelayMock<'_> {
+    fn record(&mut self, ms: u32) {
+        self.recorder.log.borrow_mut().push(Entry::Delay { ms });
+    }
+}
+
+impl DelayMs<u8> for DelayMock<'_> {
+    fn delay_ms(&mut self, ms: u8) {
+        self.record(ms.into());
+    }
+}
+
+impl embedded_hal_1::delay::DelayNs for DelayMock<'_> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.record(ns.div_ceil(1_000_000));
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.record(ms);
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for DelayMock<'_> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.record(ns.div_ceil(1_000_000));
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.record(ms);
+    }
+}